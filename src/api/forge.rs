@@ -0,0 +1,209 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::{gh::GitHub, git, gitea::Gitea, gitlab::GitLab, tags::GitTags},
+    config::Config,
+    error::Result,
+};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct Release {
+    pub name: String,
+
+    #[serde(rename = "tagName")]
+    pub tag_name: String,
+
+    #[serde(rename = "publishedAt")]
+    pub published_at: String,
+
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+
+    #[serde(rename = "isDraft")]
+    pub is_draft: bool,
+
+    #[serde(rename = "isPrerelease")]
+    pub is_prerelease: bool,
+
+    #[serde(rename = "isLatest")]
+    pub is_latest: bool,
+}
+
+/// A release to create, tagged with its title
+#[derive(Debug, Clone, Default)]
+pub struct NewRelease {
+    pub title: String,
+    pub description: String,
+    pub prerelease: bool,
+    /// Create it unpublished. A draft gets its tag when it's published.
+    pub draft: bool,
+    /// Commit to tag, the default branch if none
+    pub target: Option<String>,
+    /// Mark it as the latest release, or explicitly not. None lets the forge decide. Ignored by
+    /// forges without a latest release.
+    pub latest: Option<bool>,
+    /// Files to upload with the release
+    pub assets: Vec<PathBuf>,
+    /// The tag was created and pushed beforehand, e.g. to sign it, so the release goes on it
+    pub tag_exists: bool,
+}
+
+impl NewRelease {
+    /// Message of the annotated tag, the description or else the title
+    pub fn tag_message(&self) -> &str {
+        if self.description.is_empty() {
+            &self.title
+        } else {
+            &self.description
+        }
+    }
+}
+
+/// A release that was created
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreatedRelease {
+    /// Empty if the release has no URL
+    pub url: String,
+    /// Download URLs of the uploaded assets
+    pub asset_urls: Vec<String>,
+}
+
+/// A host for the releases of the repository, such as GitHub or GitLab
+pub trait Forge {
+    /// Fail if the forge can't be used, e.g. its CLI is missing, unsupported or not logged in
+    fn verify_auth(&self) -> Result<()>;
+
+    /// Every release of the repository
+    fn list_releases(&self) -> Result<Vec<Release>>;
+
+    /// Whether releases can be created as drafts
+    fn supports_drafts(&self) -> bool {
+        true
+    }
+
+    /// Whether files can be uploaded with a release
+    fn supports_assets(&self) -> bool {
+        true
+    }
+
+    /// Create a release and upload its assets
+    fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease>;
+
+    /// Delete a release and its tag. A draft has no tag yet.
+    fn delete_release(&self, tag: &str, is_draft: bool) -> Result<()>;
+
+    /// Publish a draft release, or turn a release back into a draft, and return its URL. A
+    /// `target` commit replaces the one the draft was created at.
+    fn set_release_draft(&self, tag: &str, draft: bool, target: Option<&str>) -> Result<String>;
+}
+
+/// The forges rema can release to, set with `forge` in `rema.json`
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+    /// Gitea or Forgejo
+    #[serde(alias = "forgejo")]
+    Gitea,
+    /// Annotated git tags only, without releases on a forge
+    Tags,
+}
+
+impl ForgeKind {
+    /// Guess the forge from the URL of a git remote, GitHub unless the host looks like GitLab,
+    /// Gitea or Forgejo
+    pub fn from_remote_url(url: &str) -> Self {
+        let host = remote_host(url);
+
+        if host.contains("gitlab") {
+            ForgeKind::GitLab
+        } else if host.contains("gitea") || host.contains("forgejo") || host == "codeberg.org" {
+            ForgeKind::Gitea
+        } else {
+            ForgeKind::GitHub
+        }
+    }
+}
+
+/// Host of a git remote URL, e.g. `github.com` for `git@github.com:owner/repo.git`
+pub fn remote_host(url: &str) -> &str {
+    let host = url
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(url)
+        .split(['/', ':'])
+        .next()
+        .unwrap_or_default();
+    host.rsplit('@').next().unwrap_or(host)
+}
+
+/// Percent-encode a tag for use in an API path, e.g. `@scope/pkg@v1.0.0`
+pub fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// The forge from the config, or else from the `origin` remote
+pub fn detect_forge(config: &Config) -> Result<Box<dyn Forge>> {
+    let kind = match config.forge {
+        Some(kind) => kind,
+        None => ForgeKind::from_remote_url(&git::get_remote_url()?),
+    };
+
+    Ok(match kind {
+        ForgeKind::GitHub => Box::new(GitHub),
+        ForgeKind::GitLab => Box::new(GitLab),
+        ForgeKind::Gitea => Box::new(Gitea::from_remote_url(
+            &git::get_remote_url()?,
+            config.forge_url.as_deref(),
+        )?),
+        ForgeKind::Tags => Box::new(GitTags),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_detect_forge_from_remote_url() {
+        let cases = [
+            ("git@github.com:owner/repo.git", ForgeKind::GitHub),
+            ("https://github.com/owner/repo", ForgeKind::GitHub),
+            ("git@gitlab.com:group/sub/repo.git", ForgeKind::GitLab),
+            (
+                "https://gitlab.example.org/group/repo.git",
+                ForgeKind::GitLab,
+            ),
+            (
+                "ssh://git@gitlab.internal:2222/group/repo.git",
+                ForgeKind::GitLab,
+            ),
+            ("https://codeberg.org/owner/repo.git", ForgeKind::Gitea),
+            ("git@forgejo.example.org:owner/repo.git", ForgeKind::Gitea),
+            ("https://git.example.org/gitlab/repo.git", ForgeKind::GitHub),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(ForgeKind::from_remote_url(url), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn should_encode_scoped_tags_for_api_paths() {
+        assert_eq!(
+            encode_path_segment("@scope/pkg@v1.0.0"),
+            "%40scope%2Fpkg%40v1.0.0"
+        );
+    }
+}
@@ -1,147 +1,273 @@
-use std::{error::Error, process::Command, str};
+use std::{process::Command, str};
 
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 
-use crate::transform::ReleaseInfo;
+use crate::{
+    api::forge::{CreatedRelease, Forge, NewRelease, Release},
+    error::{RemaError, Result},
+};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct Release {
-    pub name: String,
+/// Releases on GitHub through the `gh` CLI
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitHub;
 
-    #[serde(rename = "tagName")]
-    pub tag_name: String,
-
-    #[serde(rename = "publishedAt")]
-    pub published_at: String,
+impl Forge for GitHub {
+    fn verify_auth(&self) -> Result<()> {
+        verify_gh_cli_version()?;
+        verify_gh_auth()
+    }
 
-    #[serde(rename = "createdAt")]
-    pub created_at: String,
+    fn list_releases(&self) -> Result<Vec<Release>> {
+        list_releases()
+    }
 
-    #[serde(rename = "isDraft")]
-    pub is_draft: bool,
+    fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease> {
+        create_release(release)
+    }
 
-    #[serde(rename = "isPrerelease")]
-    pub is_prerelease: bool,
+    fn delete_release(&self, tag: &str, is_draft: bool) -> Result<()> {
+        delete_release(tag, is_draft)
+    }
 
-    #[serde(rename = "isLatest")]
-    pub is_latest: bool,
+    fn set_release_draft(&self, tag: &str, draft: bool, target: Option<&str>) -> Result<String> {
+        set_release_draft(tag, draft, target)
+    }
 }
 
 const GH_CLI_MIN_VERSION: &str = "2.45.0";
 const GH_CLI_MAX_VERSION: &str = "3.0.0";
 
-impl Default for Release {
-    fn default() -> Self {
-        Release {
-            name: String::new(),
-            tag_name: String::new(),
-            published_at: String::new(),
-            created_at: String::new(),
-            is_draft: false,
-            is_prerelease: false,
-            is_latest: false,
-        }
-    }
-}
-
-pub fn verify_gh_cli_version() -> Result<bool, Box<dyn Error>> {
+pub fn verify_gh_cli_version() -> Result<()> {
     let output = Command::new("gh")
         .arg("--version")
         .output()
-        .map_err(|e| format!("Failed to execute GitHub CLI: {}", e))?;
+        .map_err(|e| RemaError::Forge(format!("GitHub CLI is not installed: {}", e)))?;
 
     if !output.status.success() {
-        return Ok(false);
+        return Err(RemaError::Forge("GitHub CLI is not installed".to_string()));
     }
 
     let version_str = String::from_utf8_lossy(&output.stdout);
     let version = version_str
         .split_whitespace()
         .nth(2)
-        .ok_or("Failed to parse GitHub CLI version")?;
+        .ok_or(RemaError::Forge(
+            "Failed to parse GitHub CLI version".to_string(),
+        ))?;
 
-    let min_version = semver::Version::parse(GH_CLI_MIN_VERSION)?;
-    let max_version = semver::Version::parse(GH_CLI_MAX_VERSION)?;
-    let current_version = semver::Version::parse(version)?;
+    let min_version = semver::Version::parse(GH_CLI_MIN_VERSION).unwrap();
+    let max_version = semver::Version::parse(GH_CLI_MAX_VERSION).unwrap();
+    let current_version = semver::Version::parse(version).map_err(|e| {
+        RemaError::Forge(format!(
+            "Failed to parse GitHub CLI version {}: {}",
+            version, e
+        ))
+    })?;
 
     if current_version < min_version {
-        return Err(format!(
+        return Err(RemaError::Forge(format!(
             "GitHub CLI version is too old: {}. Minimum required version is {}.",
             current_version, min_version
-        )
-        .into());
+        )));
     }
 
     if current_version >= max_version {
-        return Err(format!(
+        return Err(RemaError::Forge(format!(
             "GitHub CLI version is too new: {}. Maximum supported version is {}.",
             current_version, max_version
-        )
-        .into());
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn verify_gh_auth() -> Result<()> {
+    let output = Command::new("gh")
+        .args(["auth", "status"])
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitHub CLI: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Forge(
+            "GitHub CLI is not logged in. Run 'gh auth login' first".to_string(),
+        ));
     }
 
-    Ok(true)
+    Ok(())
 }
 
-pub fn list_releases() -> Result<Vec<Release>, Box<dyn Error>> {
+pub fn list_releases() -> Result<Vec<Release>> {
     let output = Command::new("gh")
-        .args(&[
+        .args([
             "release",
             "list",
             "--json",
             "createdAt,isDraft,isLatest,isPrerelease,name,publishedAt,tagName",
         ])
         .output()
-        .map_err(|e| format!("Failed to execute GitHub CLI: {}", e))?;
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitHub CLI: {}", e)))?;
 
     if !output.status.success() {
-        return Err(format!(
+        return Err(RemaError::Forge(format!(
             "GitHub CLI returned non-success status: {}",
             String::from_utf8_lossy(&output.stderr)
-        )
-        .into());
+        )));
     }
 
     let releases: Vec<Release> = serde_json::from_slice(&output.stdout)
-        .map_err(|e| format!("Failed to parse releases: {}", e))?;
+        .map_err(|e| RemaError::Forge(format!("Failed to parse releases: {}", e)))?;
 
     Ok(releases)
 }
 
-pub fn create_release(
-    release_info: &ReleaseInfo,
-    target_description: String,
-    target_title: String,
-) -> Result<(), Box<dyn Error>> {
+/// A release asset as returned by `gh release view --json assets`
+#[derive(Debug, Deserialize)]
+struct GitHubAsset {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubReleaseAssets {
+    assets: Vec<GitHubAsset>,
+}
+
+/// Create the release and upload its assets, which `gh` does before publishing it
+pub fn create_release(release: &NewRelease) -> Result<CreatedRelease> {
+    let latest_arg = release.latest.map(|latest| format!("--latest={}", latest));
+    let asset_args: Vec<String> = release
+        .assets
+        .iter()
+        .map(|asset| asset.to_string_lossy().to_string())
+        .collect();
     let mut command_args = vec![
         "release",
         "create",
-        &target_title,
+        &release.title,
         "--title",
-        &target_title,
+        &release.title,
         "--generate-notes",
     ];
 
-    if !target_description.is_empty() {
+    if !release.description.is_empty() {
         command_args.push("--notes");
-        command_args.push(&target_description);
+        command_args.push(&release.description);
     }
 
-    if !release_info.version.pre.is_empty() {
+    if release.prerelease {
         command_args.push("--prerelease");
     }
 
-    println!("DEBUG: {:?}", command_args);
+    if release.draft {
+        command_args.push("--draft");
+    }
+
+    if let Some(target) = &release.target {
+        command_args.push("--target");
+        command_args.push(target);
+    }
+
+    // Use the tag that was pushed instead of creating one
+    if release.tag_exists {
+        command_args.push("--verify-tag");
+    }
+
+    if let Some(latest_arg) = &latest_arg {
+        command_args.push(latest_arg);
+    }
+
+    command_args.extend(asset_args.iter().map(String::as_str));
+
+    let output = Command::new("gh")
+        .args(&command_args)
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitHub CLI: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Forge(format!(
+            "Failed to create release {}: {}",
+            release.title, stderr
+        )));
+    }
+
+    // gh prints the URL of the new release
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let asset_urls = if release.assets.is_empty() {
+        Vec::new()
+    } else {
+        list_release_asset_urls(&release.title)?
+    };
+
+    Ok(CreatedRelease { url, asset_urls })
+}
+
+fn list_release_asset_urls(tag: &str) -> Result<Vec<String>> {
+    let output = Command::new("gh")
+        .args(["release", "view", tag, "--json", "assets"])
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitHub CLI: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Forge(format!(
+            "Failed to list assets of {}: {}",
+            tag,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let release: GitHubReleaseAssets = serde_json::from_slice(&output.stdout)
+        .map_err(|e| RemaError::Forge(format!("Failed to parse release assets: {}", e)))?;
+
+    Ok(release.assets.into_iter().map(|asset| asset.url).collect())
+}
+
+/// Delete a release and its tag, used to roll back a release that was created in a failed run.
+/// Drafts have no tag to clean up.
+pub fn delete_release(tag: &str, is_draft: bool) -> Result<()> {
+    let mut command_args = vec!["release", "delete", tag, "--yes"];
+    if !is_draft {
+        command_args.push("--cleanup-tag");
+    }
 
     let output = Command::new("gh")
         .args(&command_args)
         .output()
-        .expect("Failed to execute gh release create");
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitHub CLI: {}", e)))?;
 
     if !output.status.success() {
-        let stderr = str::from_utf8(&output.stderr)?;
-        return Err(format!("Git push failed: {}", stderr).into());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Forge(format!(
+            "Failed to delete release {}: {}",
+            tag, stderr
+        )));
     }
 
     Ok(())
 }
+
+/// Publish a draft release, which creates its tag at `target` or the commit the draft was
+/// created at, or turn a release back into a draft
+pub fn set_release_draft(tag: &str, draft: bool, target: Option<&str>) -> Result<String> {
+    let draft_arg = format!("--draft={}", draft);
+    let mut command_args = vec!["release", "edit", tag, &draft_arg];
+    if let Some(target) = target {
+        command_args.push("--target");
+        command_args.push(target);
+    }
+
+    let output = Command::new("gh")
+        .args(&command_args)
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitHub CLI: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Forge(format!(
+            "Failed to edit release {}: {}",
+            tag, stderr
+        )));
+    }
+
+    // gh prints the URL of the edited release
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
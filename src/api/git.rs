@@ -1,4 +1,6 @@
-use std::{error::Error, process::Command, str};
+use std::{path::Path, process::Command, str};
+
+use crate::error::{RemaError, Result};
 
 const GIT_MIN_VERSION: &str = "2.43.0";
 const GIT_MAX_VERSION: &str = "3.0.0";
@@ -9,104 +11,106 @@ pub struct CommitInfo {
     pub _message: String,
 }
 
-pub fn verify_git_version() -> Result<bool, Box<dyn Error>> {
+pub fn verify_git_version() -> Result<()> {
     let output = Command::new("git")
         .arg("--version")
         .output()
-        .map_err(|e| format!("Failed to execute GitHub CLI: {}", e))?;
+        .map_err(|e| RemaError::Git(format!("Git is not installed: {}", e)))?;
 
     if !output.status.success() {
-        return Ok(false);
+        return Err(RemaError::Git("Git is not installed".to_string()));
     }
 
     let version_str = String::from_utf8_lossy(&output.stdout);
     let version = version_str
         .split_whitespace()
         .nth(2)
-        .ok_or("Failed to parse Git version")?;
+        .ok_or(RemaError::Git("Failed to parse Git version".to_string()))?;
 
-    let min_version = semver::Version::parse(GIT_MIN_VERSION)?;
-    let max_version = semver::Version::parse(GIT_MAX_VERSION)?;
-    let current_version = semver::Version::parse(version)?;
+    let min_version = semver::Version::parse(GIT_MIN_VERSION).unwrap();
+    let max_version = semver::Version::parse(GIT_MAX_VERSION).unwrap();
+    let current_version = semver::Version::parse(version)
+        .map_err(|e| RemaError::Git(format!("Failed to parse Git version {}: {}", version, e)))?;
 
     if current_version < min_version {
-        return Err(format!(
+        return Err(RemaError::Git(format!(
             "Git version is too old: {}. Minimum required version is {}.",
             current_version, min_version
-        )
-        .into());
+        )));
     }
 
     if current_version >= max_version {
-        return Err(format!(
+        return Err(RemaError::Git(format!(
             "Git version is too new: {}. Maximum supported version is {}.",
             current_version, max_version
-        )
-        .into());
+        )));
     }
 
-    Ok(true)
+    Ok(())
 }
 
-pub fn verify_no_outstanding_commits() -> Result<(), Box<dyn Error>> {
+pub fn verify_no_outstanding_commits() -> Result<()> {
     let output = Command::new("git")
         .arg("status")
         .arg("--porcelain")
         .output()
-        .expect("Failed to execute git status");
+        .map_err(|e| RemaError::Git(format!("Failed to execute git status: {}", e)))?;
 
     if !output.stdout.is_empty() {
-        return Err(format!(
-            "Kindly commit into git any outstanding changes before proceeding. Run 'git status' to see the changes"
-        )
-        .into());
+        Err(RemaError::Git("Kindly commit into git any outstanding changes before proceeding. Run 'git status' to see the changes".to_string()))
     } else {
         Ok(())
     }
 }
 
-/// Create a release commit with the target version as message.
-///
-/// Will handle and verify staging.
-pub fn create_release_commit(target_title: &String) -> Result<CommitInfo, Box<dyn Error>> {
+/// Stage everything and commit it with the target version as message, signed with the key if one
+/// is given
+pub fn create_release_commit(
+    target_title: &String,
+    signing_key: Option<&str>,
+) -> Result<CommitInfo> {
     // Stage all changes using -A flag
     let stage_result = Command::new("git")
         .args(["add", "-A"])
         .output()
-        .map_err(|e| format!("Failed to stage changes: {}", e))?;
+        .map_err(|e| RemaError::Git(format!("Failed to stage changes: {}", e)))?;
 
     if !stage_result.status.success() {
         let stderr = str::from_utf8(&stage_result.stderr)
-            .map_err(|e| format!("Failed to parse git staging error: {}", e))?;
-        return Err(format!("Failed to stage changes: {}", stderr).into());
+            .map_err(|e| RemaError::Git(format!("Failed to parse git staging error: {}", e)))?;
+        return Err(RemaError::Git(format!(
+            "Failed to stage changes: {}",
+            stderr
+        )));
     }
 
     // Verify changes were staged
     let status = Command::new("git")
         .args(["status", "--porcelain"])
         .output()
-        .map_err(|e| format!("Failed to check git status: {}", e))?;
+        .map_err(|e| RemaError::Git(format!("Failed to check git status: {}", e)))?;
 
     let status_str = str::from_utf8(&status.stdout)
-        .map_err(|e| format!("Failed to parse git status output: {}", e))?;
+        .map_err(|e| RemaError::Git(format!("Failed to parse git status output: {}", e)))?;
 
     if status_str.trim().is_empty() {
-        return Err("No changes staged for commit".into());
+        return Err(RemaError::Git("No changes staged for commit".to_string()));
     }
 
     // Run commit
     let commit_result = Command::new("git")
         .arg("commit")
         .arg("-m")
-        .arg(format!("{}", target_title))
+        .arg(target_title)
+        .args(signing_key.map(|key| format!("--gpg-sign={}", key)))
         .output()
-        .map_err(|e| format!("Failed to execute git commit: {}", e))?;
+        .map_err(|e| RemaError::Git(format!("Failed to execute git commit: {}", e)))?;
 
     if !commit_result.status.success() {
         let stderr = str::from_utf8(&commit_result.stderr)
-            .map_err(|e| format!("Failed to parse git error output: {}", e))?;
+            .map_err(|e| RemaError::Git(format!("Failed to parse git error output: {}", e)))?;
         let stdout = str::from_utf8(&commit_result.stdout)
-            .map_err(|e| format!("Failed to parse git output: {}", e))?;
+            .map_err(|e| RemaError::Git(format!("Failed to parse git output: {}", e)))?;
 
         // Combining stdout and stderr for more complete error information
         let error_msg = if stderr.trim().is_empty() {
@@ -119,21 +123,21 @@ pub fn create_release_commit(target_title: &String) -> Result<CommitInfo, Box<dy
             format!("Git commit failed: {}", stderr.trim())
         };
 
-        return Err(error_msg.into());
+        return Err(RemaError::Git(error_msg));
     }
 
     // Get the commit SHA to deal with later if we need to restore initial state
     let sha_result = Command::new("git")
         .args(["rev-parse", "HEAD"])
         .output()
-        .map_err(|e| format!("Failed to get commit SHA: {}", e))?;
+        .map_err(|e| RemaError::Git(format!("Failed to get commit SHA: {}", e)))?;
 
     if !sha_result.status.success() {
-        return Err("Failed to get commit SHA".into());
+        return Err(RemaError::Git("Failed to get commit SHA".to_string()));
     }
 
     let sha = str::from_utf8(&sha_result.stdout)
-        .map_err(|e| format!("Failed to parse commit SHA: {}", e))?
+        .map_err(|e| RemaError::Git(format!("Failed to parse commit SHA: {}", e)))?
         .trim()
         .to_string();
 
@@ -143,45 +147,340 @@ pub fn create_release_commit(target_title: &String) -> Result<CommitInfo, Box<dy
     })
 }
 
-pub fn push() -> Result<bool, Box<dyn Error>> {
+pub fn push() -> Result<bool> {
     let output = Command::new("git")
         .arg("push")
         .output()
-        .expect("Failed to execute git push");
+        .map_err(|e| RemaError::Git(format!("Failed to execute git push: {}", e)))?;
 
     if !output.status.success() {
-        let stderr = str::from_utf8(&output.stderr)?;
-        return Err(format!("Git push failed: {}", stderr).into());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Git(format!("Git push failed: {}", stderr)));
     }
 
     Ok(true)
 }
 
-pub fn fetch_tags() -> Result<(), Box<dyn Error>> {
+pub fn fetch_tags() -> Result<()> {
     let output = Command::new("git")
         .arg("fetch")
         .arg("--tags")
         .output()
-        .expect("Failed to execute git fetch");
+        .map_err(|e| RemaError::Git(format!("Failed to execute git fetch: {}", e)))?;
 
     if !output.status.success() {
-        let stderr = str::from_utf8(&output.stderr)?;
-        return Err(format!("Git fetch failed: {}", stderr).into());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Git(format!("Git fetch failed: {}", stderr)));
     }
 
     Ok(())
 }
 
-pub fn revert_local_commit(commit_info: CommitInfo) -> Result<(), Box<dyn Error>> {
+pub fn revert_local_commit(commit_info: CommitInfo) -> Result<()> {
     let revert = Command::new("git")
         .args(["revert", "--no-edit", &commit_info.sha])
         .output()
-        .map_err(|e| format!("Failed to remove remote commit: {}", e))?;
+        .map_err(|e| RemaError::Git(format!("Failed to remove remote commit: {}", e)))?;
 
     if !revert.status.success() {
         let stderr = str::from_utf8(&revert.stderr)
-            .map_err(|e| format!("Failed to parse git reset error: {}", e))?;
-        return Err(format!("Failed to revert commit: {}", stderr).into());
+            .map_err(|e| RemaError::Git(format!("Failed to parse git reset error: {}", e)))?;
+        return Err(RemaError::Git(format!(
+            "Failed to revert commit: {}",
+            stderr
+        )));
+    }
+
+    Ok(())
+}
+
+/// Commits and files that touched a directory since a tag
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangeSummary {
+    pub commits: usize,
+    pub files_changed: usize,
+}
+
+/// Count the commits and changed files in `dir` since `tag`. Without a tag, everything in the
+/// history of `dir` counts as changed.
+pub fn get_changes_since(tag: Option<&str>, dir: &str) -> Result<ChangeSummary> {
+    let range = match tag {
+        Some(tag) => format!("{}..HEAD", tag),
+        None => "HEAD".to_string(),
+    };
+
+    let log = Command::new("git")
+        .args(["log", "--format=%H", &range, "--", dir])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git log: {}", e)))?;
+
+    if !log.status.success() {
+        let stderr = String::from_utf8_lossy(&log.stderr);
+        return Err(RemaError::Git(format!("Git log failed: {}", stderr)));
+    }
+
+    let files = match tag {
+        Some(_) => Command::new("git")
+            .args(["diff", "--name-only", &range, "--", dir])
+            .output(),
+        None => Command::new("git").args(["ls-files", "--", dir]).output(),
+    }
+    .map_err(|e| RemaError::Git(format!("Failed to list changed files: {}", e)))?;
+
+    if !files.status.success() {
+        let stderr = String::from_utf8_lossy(&files.stderr);
+        return Err(RemaError::Git(format!(
+            "Failed to list changed files: {}",
+            stderr
+        )));
+    }
+
+    Ok(ChangeSummary {
+        commits: count_lines(&log.stdout),
+        files_changed: count_lines(&files.stdout),
+    })
+}
+
+fn count_lines(output: &[u8]) -> usize {
+    String::from_utf8_lossy(output)
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count()
+}
+
+/// URL of the `origin` remote
+pub fn get_remote_url() -> Result<String> {
+    let output = Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git remote: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Git(format!(
+            "Failed to get the origin remote: {}",
+            stderr
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Tags of the repository with the date they were created, newest first
+pub fn list_tags() -> Result<Vec<(String, String)>> {
+    let output = Command::new("git")
+        .args([
+            "tag",
+            "--list",
+            "--sort=-creatordate",
+            "--format=%(refname:short)%09%(creatordate:iso-strict)",
+        ])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git tag: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Git(format!("Failed to list tags: {}", stderr)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(tag, date)| (tag.to_string(), date.to_string()))
+        .collect())
+}
+
+/// Create an annotated tag on the target commit, HEAD if none, and push it to `origin`. The tag is
+/// signed with the key if one is given.
+pub fn create_and_push_tag(
+    tag: &str,
+    message: &str,
+    target: Option<&str>,
+    signing_key: Option<&str>,
+) -> Result<()> {
+    let sign_arg = match signing_key {
+        Some(key) => format!("--local-user={}", key),
+        None => "--annotate".to_string(),
+    };
+    let output = Command::new("git")
+        .args(["tag", &sign_arg, tag, "--message", message])
+        .args(target)
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git tag: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Git(format!(
+            "Failed to create tag {}: {}",
+            tag, stderr
+        )));
+    }
+
+    let output = Command::new("git")
+        .args(["push", "origin", &format!("refs/tags/{}", tag)])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git push: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Leave no local tag behind for a tag that was never released
+        let _ = Command::new("git").args(["tag", "--delete", tag]).output();
+        return Err(RemaError::Git(format!(
+            "Failed to push tag {}: {}",
+            tag, stderr
+        )));
+    }
+
+    Ok(())
+}
+
+/// The key to sign with, the configured one or git's `user.signingKey`
+pub fn get_signing_key(configured: Option<&str>) -> Result<String> {
+    if let Some(key) = configured {
+        return Ok(key.to_string());
+    }
+
+    let output = Command::new("git")
+        .args(["config", "--get", "user.signingkey"])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git config: {}", e)))?;
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if key.is_empty() {
+        return Err(RemaError::Git(
+            "Signing is enabled but no signing key is configured. Set user.signingKey in git or signing.key in rema.json".to_string(),
+        ));
+    }
+
+    Ok(key)
+}
+
+/// Delete a tag from `origin` and locally, wherever it still exists
+pub fn delete_tag(tag: &str) -> Result<()> {
+    if list_remote_tags()?
+        .iter()
+        .any(|remote_tag| remote_tag == tag)
+    {
+        let output = Command::new("git")
+            .args(["push", "origin", "--delete", &format!("refs/tags/{}", tag)])
+            .output()
+            .map_err(|e| RemaError::Git(format!("Failed to execute git push: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(RemaError::Git(format!(
+                "Failed to delete remote tag {}: {}",
+                tag, stderr
+            )));
+        }
+    }
+
+    let local_tag = Command::new("git")
+        .args([
+            "rev-parse",
+            "--quiet",
+            "--verify",
+            &format!("refs/tags/{}", tag),
+        ])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git rev-parse: {}", e)))?;
+    if !local_tag.status.success() {
+        return Ok(());
+    }
+
+    let output = Command::new("git")
+        .args(["tag", "--delete", tag])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git tag: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Git(format!(
+            "Failed to delete tag {}: {}",
+            tag, stderr
+        )));
+    }
+
+    Ok(())
+}
+
+/// Tags on the `origin` remote
+pub fn list_remote_tags() -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .args(["ls-remote", "--tags", "--refs", "origin"])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git ls-remote: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Git(format!(
+            "Failed to list remote tags: {}",
+            stderr
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once("refs/tags/"))
+        .map(|(_, tag)| tag.to_string())
+        .collect())
+}
+
+/// Content of a file at a revision, with the path relative to the current directory. None if the
+/// file doesn't exist at that revision.
+pub fn show_file(rev: &str, path: &str) -> Result<Option<String>> {
+    let output = Command::new("git")
+        .args(["show", &format!("{}:./{}", rev, path)])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git show: {}", e)))?;
+
+    if !output.status.success() {
+        return Ok(None);
+    }
+
+    Ok(Some(String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+/// Write a gzipped tarball of a directory at a commit, with its files under `prefix/`. Files are in
+/// tree order and carry the commit time, so the same commit always gives the same tarball.
+pub fn create_archive(rev: &str, dir: &str, prefix: &str, output: &Path) -> Result<()> {
+    let time = Command::new("git")
+        .args(["log", "-1", "--format=%cI", rev])
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git log: {}", e)))?;
+
+    if !time.status.success() {
+        return Err(RemaError::Git(format!(
+            "Failed to read the time of {}: {}",
+            rev,
+            String::from_utf8_lossy(&time.stderr)
+        )));
+    }
+
+    // Archiving a tree instead of a commit uses the current time unless one is given
+    let tree = match dir {
+        "." => format!("{}:./", rev),
+        dir => format!("{}:./{}", rev, dir),
+    };
+    let output = Command::new("git")
+        .args(["archive", "--format=tar.gz"])
+        .arg(format!("--prefix={}/", prefix))
+        .arg(format!(
+            "--mtime={}",
+            String::from_utf8_lossy(&time.stdout).trim()
+        ))
+        .arg("--output")
+        .arg(output)
+        .arg(tree)
+        .output()
+        .map_err(|e| RemaError::Git(format!("Failed to execute git archive: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Git(format!(
+            "Failed to archive {}: {}",
+            dir,
+            String::from_utf8_lossy(&output.stderr)
+        )));
     }
 
     Ok(())
@@ -0,0 +1,645 @@
+use std::{
+    env,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    api::forge::{encode_path_segment, CreatedRelease, Forge, NewRelease, Release},
+    error::{RemaError, Result},
+};
+
+/// Environment variable holding the access token for Gitea and Forgejo
+pub const GITEA_TOKEN_ENV: &str = "GITEA_TOKEN";
+
+const PAGE_SIZE: usize = 50;
+
+/// Releases on Gitea or Forgejo through their REST API, requested with `curl`
+#[derive(Debug, Clone)]
+pub struct Gitea {
+    base_url: String,
+    owner: String,
+    repo: String,
+    token: Option<String>,
+}
+
+/// A release as returned by the Gitea releases API
+#[derive(Debug, Deserialize)]
+struct GiteaRelease {
+    #[serde(default)]
+    id: u64,
+    #[serde(default)]
+    name: String,
+    tag_name: String,
+    #[serde(default)]
+    created_at: String,
+    published_at: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    html_url: String,
+}
+
+/// A release asset as returned by the Gitea attachments API
+#[derive(Debug, Deserialize)]
+struct GiteaAttachment {
+    browser_download_url: String,
+}
+
+impl From<GiteaRelease> for Release {
+    /// Gitea has no latest flag
+    fn from(release: GiteaRelease) -> Self {
+        Release {
+            name: release.name,
+            tag_name: release.tag_name,
+            published_at: release.published_at.unwrap_or_default(),
+            created_at: release.created_at,
+            is_draft: release.draft,
+            is_prerelease: release.prerelease,
+            is_latest: false,
+        }
+    }
+}
+
+impl Gitea {
+    pub fn new(base_url: &str, owner: &str, repo: &str, token: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            token,
+        }
+    }
+
+    /// Repository of a git remote, with the token from `GITEA_TOKEN`. The API is expected on the
+    /// host of the remote unless a base URL is given.
+    pub fn from_remote_url(url: &str, base_url: Option<&str>) -> Result<Self> {
+        let (remote_base_url, owner, repo) = parse_remote_url(url).ok_or(
+            RemaError::InvalidInput(format!("Can't find the repository in remote {}", url)),
+        )?;
+        let token = env::var(GITEA_TOKEN_ENV)
+            .ok()
+            .filter(|token| !token.is_empty());
+
+        Ok(Self::new(
+            base_url.unwrap_or(&remote_base_url),
+            &owner,
+            &repo,
+            token,
+        ))
+    }
+
+    fn repo_url(&self, path: &str) -> String {
+        format!(
+            "{}/api/v1/repos/{}/{}{}",
+            self.base_url, self.owner, self.repo, path
+        )
+    }
+
+    /// Send a request with an optional JSON body and return the response body
+    fn request(&self, method: &str, url: &str, body: Option<&str>) -> Result<Vec<u8>> {
+        match body {
+            Some(body) => self.send(
+                method,
+                url,
+                &[
+                    "--header",
+                    "Content-Type: application/json",
+                    "--data-binary",
+                    body,
+                ],
+            ),
+            None => self.send(method, url, &[]),
+        }
+    }
+
+    /// Upload a file as a multipart form, as the attachment APIs expect
+    fn upload(&self, url: &str, file: &Path) -> Result<Vec<u8>> {
+        let form = format!("attachment=@\"{}\"", file.to_string_lossy());
+        self.send("POST", url, &["--form", &form])
+    }
+
+    /// Run `curl` and return the response body, failing on anything but a 2xx status. The token
+    /// goes to `curl` through stdin to keep it out of the process list.
+    fn send(&self, method: &str, url: &str, args: &[&str]) -> Result<Vec<u8>> {
+        let mut command = Command::new("curl");
+        command
+            .args(["--silent", "--show-error", "--request", method])
+            .args(["--write-out", "\n%{http_code}", "--config", "-"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command.arg(url);
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| RemaError::Forge(format!("Failed to execute curl: {}", e)))?;
+
+        let mut config = String::new();
+        if let Some(token) = &self.token {
+            let token = token.replace('\\', "\\\\").replace('"', "\\\"");
+            config.push_str(&format!("header = \"Authorization: token {}\"\n", token));
+        }
+        child
+            .stdin
+            .take()
+            .ok_or(RemaError::Forge("Failed to open curl stdin".to_string()))?
+            .write_all(config.as_bytes())
+            .map_err(|e| RemaError::Forge(format!("Failed to configure curl: {}", e)))?;
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| RemaError::Forge(format!("Failed to execute curl: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(RemaError::Forge(format!(
+                "Request to {} failed: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let split = output
+            .stdout
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .unwrap_or(0);
+        let (response, status) = output.stdout.split_at(split);
+        let status = String::from_utf8_lossy(status).trim().to_string();
+
+        if !status.starts_with('2') {
+            return Err(RemaError::Forge(format!(
+                "{} {} returned {}: {}",
+                method,
+                url,
+                status,
+                String::from_utf8_lossy(response)
+            )));
+        }
+
+        Ok(response.to_vec())
+    }
+
+    /// Releases are edited by id, and the lookup by tag doesn't find drafts
+    fn find_release_id(&self, tag: &str) -> Result<u64> {
+        self.list_gitea_releases()?
+            .into_iter()
+            .find(|release| release.tag_name == tag)
+            .map(|release| release.id)
+            .ok_or(RemaError::Forge(format!("No release for tag {}", tag)))
+    }
+
+    /// Create the release and its tag on the default branch, then upload its assets. Gitea doesn't
+    /// generate release notes, so the description is the whole of them.
+    fn create_gitea_release(&self, release: &NewRelease) -> Result<CreatedRelease> {
+        let mut body = json!({
+            "tag_name": release.title,
+            "name": release.title,
+            "body": release.description,
+            "prerelease": release.prerelease,
+            "draft": release.draft,
+        });
+        if let Some(target) = &release.target {
+            body["target_commitish"] = json!(target);
+        }
+        let body = body.to_string();
+
+        let response = self.request("POST", &self.repo_url("/releases"), Some(&body))?;
+        let created: GiteaRelease = serde_json::from_slice(&response)
+            .map_err(|e| RemaError::Forge(format!("Failed to parse release: {}", e)))?;
+
+        // The release isn't recorded as created until this returns, so a failed upload cleans
+        // up after itself
+        let asset_urls = match self.upload_assets(created.id, &release.assets) {
+            Ok(asset_urls) => asset_urls,
+            Err(e) => {
+                self.delete_release(&release.title, release.draft)?;
+                return Err(e);
+            }
+        };
+
+        Ok(CreatedRelease {
+            url: created.html_url,
+            asset_urls,
+        })
+    }
+
+    /// Upload files to a release and return their download URLs
+    fn upload_assets(&self, id: u64, assets: &[PathBuf]) -> Result<Vec<String>> {
+        let mut urls = Vec::new();
+        for asset in assets {
+            let name = asset
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let url = self.repo_url(&format!(
+                "/releases/{}/assets?name={}",
+                id,
+                encode_path_segment(&name)
+            ));
+            let response = self.upload(&url, asset)?;
+            let attachment: GiteaAttachment = serde_json::from_slice(&response)
+                .map_err(|e| RemaError::Forge(format!("Failed to parse asset: {}", e)))?;
+            urls.push(attachment.browser_download_url);
+        }
+
+        Ok(urls)
+    }
+
+    fn list_gitea_releases(&self) -> Result<Vec<GiteaRelease>> {
+        let mut releases = Vec::new();
+        for page in 1.. {
+            let url = self.repo_url(&format!("/releases?page={}&limit={}", page, PAGE_SIZE));
+            let response = self.request("GET", &url, None)?;
+            let page_releases: Vec<GiteaRelease> = serde_json::from_slice(&response)
+                .map_err(|e| RemaError::Forge(format!("Failed to parse releases: {}", e)))?;
+
+            let is_last_page = page_releases.len() < PAGE_SIZE;
+            releases.extend(page_releases);
+            if is_last_page {
+                break;
+            }
+        }
+
+        Ok(releases)
+    }
+}
+
+impl Forge for Gitea {
+    fn verify_auth(&self) -> Result<()> {
+        if self.token.is_none() {
+            return Err(RemaError::Forge(format!(
+                "Set {} to an access token of {}",
+                GITEA_TOKEN_ENV, self.base_url
+            )));
+        }
+
+        self.request("GET", &format!("{}/api/v1/user", self.base_url), None)
+            .map_err(|e| RemaError::Forge(format!("Failed to authenticate: {}", e)))?;
+
+        Ok(())
+    }
+
+    fn list_releases(&self) -> Result<Vec<Release>> {
+        Ok(self
+            .list_gitea_releases()?
+            .into_iter()
+            .map(Release::from)
+            .collect())
+    }
+
+    fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease> {
+        self.create_gitea_release(release)
+    }
+
+    /// Delete a release, then its tag, which Gitea keeps otherwise. Drafts are deleted by id as
+    /// the lookup by tag doesn't find them.
+    fn delete_release(&self, tag: &str, is_draft: bool) -> Result<()> {
+        if is_draft {
+            let id = self.find_release_id(tag)?;
+            self.request("DELETE", &self.repo_url(&format!("/releases/{}", id)), None)?;
+            return Ok(());
+        }
+
+        let tag = encode_path_segment(tag);
+        self.request(
+            "DELETE",
+            &self.repo_url(&format!("/releases/tags/{}", tag)),
+            None,
+        )?;
+        self.request("DELETE", &self.repo_url(&format!("/tags/{}", tag)), None)?;
+
+        Ok(())
+    }
+
+    fn set_release_draft(&self, tag: &str, draft: bool, target: Option<&str>) -> Result<String> {
+        let id = self.find_release_id(tag)?;
+        let mut body = json!({ "draft": draft });
+        if let Some(target) = target {
+            body["target_commitish"] = json!(target);
+        }
+        let body = body.to_string();
+        let response = self.request(
+            "PATCH",
+            &self.repo_url(&format!("/releases/{}", id)),
+            Some(&body),
+        )?;
+        let release: GiteaRelease = serde_json::from_slice(&response)
+            .map_err(|e| RemaError::Forge(format!("Failed to parse release: {}", e)))?;
+
+        Ok(release.html_url)
+    }
+}
+
+/// Base URL, owner and repository of a remote. HTTP remotes keep their scheme, port and any
+/// path prefix; SSH remotes are assumed to serve the API over HTTPS on the same host.
+fn parse_remote_url(url: &str) -> Option<(String, String, String)> {
+    let (base_url, path) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let (authority, path) = rest.split_once('/')?;
+            let host = authority.rsplit('@').next()?;
+            match scheme {
+                "http" | "https" => (format!("{}://{}", scheme, host), path),
+                _ => (format!("https://{}", host.split(':').next()?), path),
+            }
+        }
+        None => {
+            let (authority, path) = url.split_once(':')?;
+            (format!("https://{}", authority.rsplit('@').next()?), path)
+        }
+    };
+
+    let mut segments: Vec<&str> = path
+        .trim_end_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let repo = segments.pop()?.trim_end_matches(".git").to_string();
+    let owner = segments.pop()?.to_string();
+    let base_url = if url.starts_with("http") && !segments.is_empty() {
+        format!("{}/{}", base_url, segments.join("/"))
+    } else {
+        base_url
+    };
+
+    Some((base_url, owner, repo))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{BufRead, BufReader, Read},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use super::*;
+
+    /// Serves one canned response per request, in order, and records the requests
+    fn start_mock_server(responses: Vec<(u16, String)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut authorization = String::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+                    let (name, value) = header.split_once(':').unwrap();
+                    match name.to_lowercase().as_str() {
+                        "content-length" => content_length = value.trim().parse().unwrap(),
+                        "authorization" => authorization = value.trim().to_string(),
+                        _ => {}
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+
+                let request_line: Vec<&str> = request_line.split_whitespace().collect();
+                recorded.lock().unwrap().push(
+                    format!(
+                        "{} {} [{}] {}",
+                        request_line[0],
+                        request_line[1],
+                        authorization,
+                        String::from_utf8_lossy(&request_body)
+                    )
+                    .trim_end()
+                    .to_string(),
+                );
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (base_url, requests)
+    }
+
+    fn release_json(tag: &str, draft: bool, prerelease: bool) -> serde_json::Value {
+        json!({
+            "id": 1,
+            "name": tag,
+            "tag_name": tag,
+            "created_at": "2024-01-01T00:00:00Z",
+            "published_at": if draft { None } else { Some("2024-01-02T00:00:00Z") },
+            "draft": draft,
+            "prerelease": prerelease,
+            "html_url": format!("https://forgejo.example.org/owner/repo/releases/tag/{}", tag),
+        })
+    }
+
+    #[test]
+    fn should_list_releases_across_pages() {
+        let first_page: Vec<serde_json::Value> = (0..PAGE_SIZE)
+            .map(|patch| release_json(&format!("core@v1.0.{}", patch), false, false))
+            .collect();
+        let second_page = vec![release_json("core@v1.1.0-beta.0", true, true)];
+        let (base_url, requests) = start_mock_server(vec![
+            (200, json!(first_page).to_string()),
+            (200, json!(second_page).to_string()),
+        ]);
+        let gitea = Gitea::new(&base_url, "owner", "repo", Some("secret".to_string()));
+
+        let releases = gitea.list_releases().unwrap();
+
+        assert_eq!(releases.len(), PAGE_SIZE + 1);
+        assert_eq!(releases[0].tag_name, "core@v1.0.0");
+        assert_eq!(releases[0].published_at, "2024-01-02T00:00:00Z");
+        let beta = &releases[PAGE_SIZE];
+        assert!(beta.is_draft && beta.is_prerelease);
+        assert_eq!(beta.published_at, "");
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                "GET /api/v1/repos/owner/repo/releases?page=1&limit=50 [token secret]",
+                "GET /api/v1/repos/owner/repo/releases?page=2&limit=50 [token secret]",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_create_and_delete_releases() {
+        let (base_url, requests) = start_mock_server(vec![
+            (
+                201,
+                release_json("@scope/core@v2.0.0-rc.0", false, true).to_string(),
+            ),
+            (204, String::new()),
+            (204, String::new()),
+        ]);
+        let gitea = Gitea::new(&base_url, "owner", "repo", None);
+        let release = NewRelease {
+            title: "@scope/core@v2.0.0-rc.0".to_string(),
+            description: "Notes".to_string(),
+            prerelease: true,
+            ..Default::default()
+        };
+
+        let url = gitea.create_release(&release).unwrap().url;
+        gitea
+            .delete_release("@scope/core@v2.0.0-rc.0", false)
+            .unwrap();
+
+        assert_eq!(
+            url,
+            "https://forgejo.example.org/owner/repo/releases/tag/@scope/core@v2.0.0-rc.0"
+        );
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST /api/v1/repos/owner/repo/releases [] {"));
+        let body: serde_json::Value =
+            serde_json::from_str(requests[0].split_once("[] ").unwrap().1).unwrap();
+        assert_eq!(body["tag_name"], "@scope/core@v2.0.0-rc.0");
+        assert_eq!(body["prerelease"], true);
+        assert_eq!(body["draft"], false);
+        assert_eq!(
+            requests[1],
+            "DELETE /api/v1/repos/owner/repo/releases/tags/%40scope%2Fcore%40v2.0.0-rc.0 []"
+        );
+        assert_eq!(
+            requests[2],
+            "DELETE /api/v1/repos/owner/repo/tags/%40scope%2Fcore%40v2.0.0-rc.0 []"
+        );
+    }
+
+    #[test]
+    fn should_publish_draft_at_release_commit() {
+        let (base_url, requests) = start_mock_server(vec![
+            (
+                200,
+                json!([release_json("core@v1.1.0", true, false)]).to_string(),
+            ),
+            (200, release_json("core@v1.1.0", false, false).to_string()),
+        ]);
+        let gitea = Gitea::new(&base_url, "owner", "repo", None);
+
+        let url = gitea
+            .set_release_draft("core@v1.1.0", false, Some("abc123"))
+            .unwrap();
+
+        assert!(url.ends_with("/releases/tag/core@v1.1.0"));
+        let requests = requests.lock().unwrap();
+        assert!(requests[1].starts_with("PATCH /api/v1/repos/owner/repo/releases/1 [] {"));
+        let body: serde_json::Value =
+            serde_json::from_str(requests[1].split_once("[] ").unwrap().1).unwrap();
+        assert_eq!(
+            body,
+            json!({ "draft": false, "target_commitish": "abc123" })
+        );
+    }
+
+    #[test]
+    fn should_upload_assets_and_clean_up_failed_uploads() {
+        let asset = env::temp_dir().join(format!("rema-gitea-asset-{}.tgz", std::process::id()));
+        fs::write(&asset, "tarball").unwrap();
+        let (base_url, requests) = start_mock_server(vec![
+            (201, release_json("core@v1.0.0", false, false).to_string()),
+            (
+                201,
+                json!({ "browser_download_url": "https://forgejo.example.org/attachments/1" })
+                    .to_string(),
+            ),
+            (201, release_json("core@v1.0.1", false, false).to_string()),
+            (413, r#"{"message":"too large"}"#.to_string()),
+            (204, String::new()),
+            (204, String::new()),
+        ]);
+        let gitea = Gitea::new(&base_url, "owner", "repo", None);
+        let release = |title: &str| NewRelease {
+            title: title.to_string(),
+            assets: vec![asset.clone()],
+            ..Default::default()
+        };
+
+        let created = gitea.create_release(&release("core@v1.0.0")).unwrap();
+        let failed = gitea.create_release(&release("core@v1.0.1"));
+
+        fs::remove_file(&asset).unwrap();
+        assert_eq!(
+            created.asset_urls,
+            vec!["https://forgejo.example.org/attachments/1"]
+        );
+        assert!(failed.is_err());
+        let requests = requests.lock().unwrap();
+        let file_name = asset.file_name().unwrap().to_string_lossy();
+        assert!(requests[1].starts_with(&format!(
+            "POST /api/v1/repos/owner/repo/releases/1/assets?name={} []",
+            file_name
+        )));
+        assert!(requests[1].contains("name=\"attachment\""));
+        assert!(requests[1].contains("tarball"));
+        assert_eq!(
+            requests[4],
+            "DELETE /api/v1/repos/owner/repo/releases/tags/core%40v1.0.1 []"
+        );
+    }
+
+    #[test]
+    fn should_fail_on_error_status() {
+        let (base_url, _) = start_mock_server(vec![(
+            401,
+            r#"{"message":"token is required"}"#.to_string(),
+        )]);
+        let gitea = Gitea::new(&base_url, "owner", "repo", Some("bad".to_string()));
+
+        let result = gitea.verify_auth();
+
+        assert!(
+            matches!(result, Err(RemaError::Forge(msg)) if msg.contains("401") && msg.contains("token is required"))
+        );
+    }
+
+    #[test]
+    fn should_parse_remote_urls() {
+        let cases = [
+            (
+                "git@codeberg.org:owner/repo.git",
+                ("https://codeberg.org", "owner", "repo"),
+            ),
+            (
+                "ssh://git@forgejo.internal:2222/owner/repo.git",
+                ("https://forgejo.internal", "owner", "repo"),
+            ),
+            (
+                "http://localhost:3000/git/owner/repo",
+                ("http://localhost:3000/git", "owner", "repo"),
+            ),
+        ];
+
+        for (url, (base_url, owner, repo)) in cases {
+            assert_eq!(
+                parse_remote_url(url),
+                Some((base_url.to_string(), owner.to_string(), repo.to_string())),
+                "{}",
+                url
+            );
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::{
+    api::forge::{encode_path_segment, CreatedRelease, Forge, NewRelease, Release},
+    error::{RemaError, Result},
+};
+
+const GLAB_CLI_MIN_VERSION: &str = "1.30.0";
+const GLAB_CLI_MAX_VERSION: &str = "2.0.0";
+
+/// Releases on GitLab, including self-hosted instances, through the `glab` CLI. The project is
+/// resolved by `glab` from the git remotes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitLab;
+
+impl Forge for GitLab {
+    fn verify_auth(&self) -> Result<()> {
+        verify_glab_cli_version()?;
+        verify_glab_auth()
+    }
+
+    fn list_releases(&self) -> Result<Vec<Release>> {
+        list_releases()
+    }
+
+    fn supports_drafts(&self) -> bool {
+        false
+    }
+
+    fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease> {
+        if release.draft {
+            return Err(RemaError::Forge("GitLab has no draft releases".to_string()));
+        }
+        create_release(release)
+    }
+
+    fn delete_release(&self, tag: &str, _is_draft: bool) -> Result<()> {
+        delete_release(tag)
+    }
+
+    fn set_release_draft(&self, _tag: &str, _draft: bool, _target: Option<&str>) -> Result<String> {
+        Err(RemaError::Forge("GitLab has no draft releases".to_string()))
+    }
+}
+
+/// A release as returned by the GitLab releases API
+#[derive(Debug, Deserialize)]
+struct GitLabRelease {
+    name: Option<String>,
+    tag_name: String,
+    #[serde(default)]
+    created_at: String,
+    #[serde(default)]
+    released_at: String,
+    #[serde(rename = "_links", default)]
+    links: GitLabReleaseLinks,
+    #[serde(default)]
+    assets: GitLabReleaseAssets,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct GitLabReleaseLinks {
+    #[serde(rename = "self", default)]
+    self_url: String,
+}
+
+/// Uploaded files are attached to a release as links
+#[derive(Debug, Deserialize, Default)]
+struct GitLabReleaseAssets {
+    #[serde(default)]
+    links: Vec<GitLabAssetLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitLabAssetLink {
+    url: String,
+}
+
+impl From<GitLabRelease> for Release {
+    /// GitLab has no drafts or latest flag, and pre-releases are only marked by their tag
+    fn from(release: GitLabRelease) -> Self {
+        let is_prerelease = release
+            .tag_name
+            .rsplit('@')
+            .next()
+            .and_then(|version| semver::Version::parse(version.trim_start_matches('v')).ok())
+            .is_some_and(|version| !version.pre.is_empty());
+
+        Release {
+            name: release.name.unwrap_or_else(|| release.tag_name.clone()),
+            tag_name: release.tag_name,
+            published_at: release.released_at,
+            created_at: release.created_at,
+            is_draft: false,
+            is_prerelease,
+            is_latest: false,
+        }
+    }
+}
+
+pub fn verify_glab_cli_version() -> Result<()> {
+    let output = Command::new("glab")
+        .arg("--version")
+        .output()
+        .map_err(|e| RemaError::Forge(format!("GitLab CLI is not installed: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Forge("GitLab CLI is not installed".to_string()));
+    }
+
+    // e.g. "glab version 1.36.0 (2024-01-18)" or "glab 1.46.1"
+    let version_str = String::from_utf8_lossy(&output.stdout);
+    let current_version = version_str
+        .split_whitespace()
+        .find_map(|word| semver::Version::parse(word.trim_start_matches('v')).ok())
+        .ok_or(RemaError::Forge(
+            "Failed to parse GitLab CLI version".to_string(),
+        ))?;
+
+    let min_version = semver::Version::parse(GLAB_CLI_MIN_VERSION).unwrap();
+    let max_version = semver::Version::parse(GLAB_CLI_MAX_VERSION).unwrap();
+
+    if current_version < min_version {
+        return Err(RemaError::Forge(format!(
+            "GitLab CLI version is too old: {}. Minimum required version is {}.",
+            current_version, min_version
+        )));
+    }
+
+    if current_version >= max_version {
+        return Err(RemaError::Forge(format!(
+            "GitLab CLI version is too new: {}. Maximum supported version is {}.",
+            current_version, max_version
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn verify_glab_auth() -> Result<()> {
+    let output = Command::new("glab")
+        .args(["auth", "status"])
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitLab CLI: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Forge(
+            "GitLab CLI is not logged in. Run 'glab auth login' first".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn glab_api(endpoint: &str, paginate: bool) -> Result<Vec<u8>> {
+    let mut args = vec!["api", endpoint];
+    if paginate {
+        args.push("--paginate");
+    }
+
+    let output = Command::new("glab")
+        .args(&args)
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitLab CLI: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Forge(format!(
+            "GitLab CLI returned non-success status: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(output.stdout)
+}
+
+pub fn list_releases() -> Result<Vec<Release>> {
+    let output = glab_api("projects/:id/releases", true)?;
+    parse_releases(&output)
+}
+
+/// Paginated output is one JSON array per page
+fn parse_releases(output: &[u8]) -> Result<Vec<Release>> {
+    let mut releases = Vec::new();
+    for page in serde_json::Deserializer::from_slice(output).into_iter::<Vec<GitLabRelease>>() {
+        let page =
+            page.map_err(|e| RemaError::Forge(format!("Failed to parse releases: {}", e)))?;
+        releases.extend(page.into_iter().map(Release::from));
+    }
+
+    Ok(releases)
+}
+
+/// Create the release and its tag, on the default branch unless a target is given, and upload its
+/// assets. GitLab doesn't generate release notes, so the description is the whole of them.
+pub fn create_release(release: &NewRelease) -> Result<CreatedRelease> {
+    let target_title = release.title.as_str();
+    let asset_args: Vec<String> = release
+        .assets
+        .iter()
+        .map(|asset| asset.to_string_lossy().to_string())
+        .collect();
+    let mut command_args = vec!["release", "create", target_title];
+    command_args.extend(asset_args.iter().map(String::as_str));
+    command_args.extend(["--name", target_title, "--notes", &release.description]);
+
+    if let Some(target) = &release.target {
+        command_args.push("--ref");
+        command_args.push(target);
+    }
+
+    let output = Command::new("glab")
+        .args(&command_args)
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitLab CLI: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Forge(format!(
+            "Failed to create release {}: {}",
+            target_title, stderr
+        )));
+    }
+
+    let release = glab_api(
+        &format!(
+            "projects/:id/releases/{}",
+            encode_path_segment(target_title)
+        ),
+        false,
+    )?;
+    let release: GitLabRelease = serde_json::from_slice(&release)
+        .map_err(|e| RemaError::Forge(format!("Failed to parse release: {}", e)))?;
+
+    Ok(CreatedRelease {
+        url: release.links.self_url,
+        asset_urls: release
+            .assets
+            .links
+            .into_iter()
+            .map(|link| link.url)
+            .collect(),
+    })
+}
+
+/// Delete a release and its tag, used to roll back a release that was created in a failed run
+pub fn delete_release(tag: &str) -> Result<()> {
+    let output = Command::new("glab")
+        .args(["release", "delete", tag, "--yes", "--with-tag"])
+        .output()
+        .map_err(|e| RemaError::Forge(format!("Failed to execute GitLab CLI: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(RemaError::Forge(format!(
+            "Failed to delete release {}: {}",
+            tag, stderr
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_map_paginated_gitlab_releases() {
+        let output = br#"[{"name":"core@v1.0.0","tag_name":"core@v1.0.0","created_at":"2024-01-01T00:00:00Z","released_at":"2024-01-01T00:00:00Z","_links":{"self":"https://gitlab.com/g/r/-/releases/core@v1.0.0"}}]
+[{"name":null,"tag_name":"core@v1.1.0-beta.0","created_at":"2024-02-01T00:00:00Z","released_at":"2024-02-01T00:00:00Z"}]"#;
+
+        let releases = parse_releases(output).unwrap();
+
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].tag_name, "core@v1.0.0");
+        assert_eq!(releases[0].published_at, "2024-01-01T00:00:00Z");
+        assert!(!releases[0].is_prerelease);
+        assert_eq!(releases[1].name, "core@v1.1.0-beta.0");
+        assert!(releases[1].is_prerelease);
+    }
+}
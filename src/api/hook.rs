@@ -0,0 +1,42 @@
+use std::{
+    io,
+    process::{Command, Stdio},
+};
+
+use crate::error::{RemaError, Result};
+
+/// Run a hook command with `sh`. Its output goes to stderr so it doesn't mix with JSON output.
+pub fn run_hook(command: &str, env: &[(String, String)]) -> Result<()> {
+    let status = Command::new("sh")
+        .args(["-c", command])
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::null())
+        .stdout(io::stderr())
+        .status()
+        .map_err(|e| RemaError::Hook(format!("Failed to run {}: {}", command, e)))?;
+
+    if !status.success() {
+        return Err(RemaError::Hook(format!(
+            "{} exited with {}",
+            command, status
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_pass_env_and_fail_on_exit_status() {
+        let env = vec![("REMA_TAG".to_string(), "core@v1.0.1".to_string())];
+
+        assert!(run_hook(r#"test "$REMA_TAG" = core@v1.0.1"#, &env).is_ok());
+        assert!(matches!(
+            run_hook("exit 3", &env),
+            Err(RemaError::Hook(msg)) if msg.contains("exit 3")
+        ));
+    }
+}
@@ -1,2 +1,9 @@
+pub(crate) mod forge;
 pub(crate) mod gh;
 pub(crate) mod git;
+pub(crate) mod gitea;
+pub(crate) mod gitlab;
+pub(crate) mod hook;
+pub(crate) mod package_manager;
+pub(crate) mod ssh;
+pub(crate) mod tags;
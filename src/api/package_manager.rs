@@ -0,0 +1,100 @@
+use std::{io, process::Command};
+
+use semver::Version;
+
+use crate::{
+    error::{RemaError, Result},
+    read::PackageManager,
+};
+
+/// Update the lockfile of the current directory to the package.json files with the package
+/// manager. Yarn 1 has no lockfile only mode, so it runs a full install without scripts. The
+/// output goes to stderr.
+pub fn regenerate_lockfile(manager: PackageManager) -> Result<()> {
+    let yarn_version = match manager {
+        PackageManager::Yarn => Some(get_yarn_version()?),
+        _ => None,
+    };
+    let (program, args) = lockfile_command(manager, yarn_version.as_ref());
+    let command = format!("{} {}", program, args.join(" "));
+
+    let output = Command::new(program)
+        .args(args)
+        .stdout(io::stderr())
+        .output()
+        .map_err(|e| RemaError::Manifest(format!("Failed to run {}: {}", command, e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Manifest(format!(
+            "{} failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Command that updates the lockfile of a package manager
+fn lockfile_command(
+    manager: PackageManager,
+    yarn_version: Option<&Version>,
+) -> (&'static str, &'static [&'static str]) {
+    match manager {
+        PackageManager::Npm => ("npm", &["install", "--package-lock-only"]),
+        PackageManager::Pnpm => ("pnpm", &["install", "--lockfile-only"]),
+        PackageManager::Yarn if yarn_version.is_some_and(|version| version.major < 2) => (
+            "yarn",
+            &["install", "--ignore-scripts", "--non-interactive"],
+        ),
+        PackageManager::Yarn => ("yarn", &["install", "--mode", "update-lockfile"]),
+    }
+}
+
+/// Version of the yarn the current directory uses, which Corepack picks from `packageManager`
+fn get_yarn_version() -> Result<Version> {
+    let output = Command::new("yarn")
+        .arg("--version")
+        .output()
+        .map_err(|e| RemaError::Manifest(format!("Failed to run yarn --version: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::Manifest(format!(
+            "yarn --version failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let version = String::from_utf8_lossy(&output.stdout);
+    Version::parse(version.trim()).map_err(|e| {
+        RemaError::Manifest(format!(
+            "Failed to parse yarn version {}: {}",
+            version.trim(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_update_lockfile_with_yarn_berry() {
+        let classic = Version::new(1, 22, 22);
+        let berry = Version::new(4, 5, 0);
+
+        assert_eq!(
+            lockfile_command(PackageManager::Yarn, Some(&classic)).1,
+            ["install", "--ignore-scripts", "--non-interactive"]
+        );
+        assert_eq!(
+            lockfile_command(PackageManager::Yarn, Some(&berry)).1,
+            ["install", "--mode", "update-lockfile"]
+        );
+        assert_eq!(
+            lockfile_command(PackageManager::Npm, None).1,
+            ["install", "--package-lock-only"]
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::error::{RemaError, Result};
+
+/// Namespace of file signatures, as expected by `ssh-keygen -Y verify -n file`
+const SIGNATURE_NAMESPACE: &str = "file";
+
+/// Fail if `ssh-keygen` is missing or the key can't be found
+pub fn verify_signing_key(key: &str) -> Result<()> {
+    Command::new("ssh-keygen")
+        .arg("-?")
+        .output()
+        .map_err(|e| RemaError::InvalidInput(format!("ssh-keygen is not installed: {}", e)))?;
+
+    if key.starts_with("key::") {
+        return Ok(());
+    }
+
+    let path = expand_home(key);
+    if !path.is_file() {
+        return Err(RemaError::InvalidInput(format!(
+            "SSH signing key {} doesn't exist. Checksums can only be signed with an SSH key",
+            path.display()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Sign a file with `ssh-keygen -Y sign` and return the path of the detached signature, which is
+/// the file path with `.sig` appended. The key is a private key, or a public key whose private
+/// key is in the SSH agent, either as a path or literally as `key::<public key>` like in git's
+/// `user.signingKey`.
+pub fn sign_file(path: &Path, key: &str) -> Result<PathBuf> {
+    let key_path = match key.strip_prefix("key::") {
+        Some(public_key) => {
+            let key_path = path.with_extension("signing-key.pub");
+            fs::write(&key_path, public_key).map_err(|e| {
+                RemaError::InvalidInput(format!("Failed to write the signing key: {}", e))
+            })?;
+            key_path
+        }
+        None => expand_home(key),
+    };
+
+    let output = Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-n", SIGNATURE_NAMESPACE, "-f"])
+        .arg(&key_path)
+        .arg(path)
+        .output()
+        .map_err(|e| RemaError::InvalidInput(format!("Failed to execute ssh-keygen: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(RemaError::InvalidInput(format!(
+            "Failed to sign {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let mut signature = path.as_os_str().to_owned();
+    signature.push(".sig");
+    Ok(PathBuf::from(signature))
+}
+
+/// Resolve a leading `~/` like git does for `user.signingKey`
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_sign_file_with_ssh_key() {
+        let dir = env::temp_dir().join(format!("rema-ssh-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let key = dir.join("id_ed25519");
+        let file = dir.join("SHA256SUMS");
+        fs::write(&file, "ab12  core-1.0.0.tgz\n").unwrap();
+        let keygen = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .output()
+            .unwrap();
+        assert!(keygen.status.success());
+
+        verify_signing_key(key.to_str().unwrap()).unwrap();
+        let signature = sign_file(&file, key.to_str().unwrap()).unwrap();
+
+        assert_eq!(signature, dir.join("SHA256SUMS.sig"));
+        assert!(fs::read_to_string(&signature)
+            .unwrap()
+            .starts_with("-----BEGIN SSH SIGNATURE-----"));
+        assert!(verify_signing_key(dir.join("missing").to_str().unwrap()).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
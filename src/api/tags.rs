@@ -0,0 +1,107 @@
+use crate::{
+    api::{
+        forge::{CreatedRelease, Forge, NewRelease, Release},
+        git,
+    },
+    error::{RemaError, Result},
+    transform,
+};
+
+/// Releases as plain annotated git tags pushed to `origin`, without any forge. Works offline
+/// against a local bare remote.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitTags;
+
+impl Forge for GitTags {
+    /// Nothing to check besides git itself
+    fn verify_auth(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn list_releases(&self) -> Result<Vec<Release>> {
+        Ok(releases_from_tags(git::list_tags()?))
+    }
+
+    fn supports_drafts(&self) -> bool {
+        false
+    }
+
+    fn supports_assets(&self) -> bool {
+        false
+    }
+
+    /// Tags have no URL, so the returned one is empty. A tag that was pushed beforehand is the
+    /// release already.
+    fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease> {
+        if release.draft {
+            return Err(RemaError::Forge("Git tags can't be drafts".to_string()));
+        }
+        if !release.assets.is_empty() {
+            return Err(RemaError::Forge("Git tags can't have assets".to_string()));
+        }
+        if !release.tag_exists {
+            git::create_and_push_tag(
+                &release.title,
+                release.tag_message(),
+                release.target.as_deref(),
+                None,
+            )?;
+        }
+
+        Ok(CreatedRelease::default())
+    }
+
+    fn delete_release(&self, tag: &str, _is_draft: bool) -> Result<()> {
+        git::delete_tag(tag)
+    }
+
+    fn set_release_draft(&self, _tag: &str, _draft: bool, _target: Option<&str>) -> Result<String> {
+        Err(RemaError::Forge("Git tags can't be drafts".to_string()))
+    }
+}
+
+/// Tags in the `name@vX.Y.Z` or `vX.Y.Z` format of rema as releases. Other tags of the repository
+/// are ignored.
+fn releases_from_tags(tags: Vec<(String, String)>) -> Vec<Release> {
+    tags.into_iter()
+        .filter_map(|(tag, date)| {
+            let (_, version) = transform::parse_release_tag(&tag)?;
+
+            Some(Release {
+                name: tag.clone(),
+                tag_name: tag,
+                published_at: date.clone(),
+                created_at: date,
+                is_draft: false,
+                is_prerelease: !version.pre.is_empty(),
+                is_latest: false,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_keep_release_tags() {
+        let tags = vec![
+            ("core@v1.1.0-rc.0", "2024-02-01T00:00:00+00:00"),
+            ("core@v1.0.0", "2024-01-01T00:00:00+00:00"),
+            ("v2.0.0", "2024-01-01T00:00:00+00:00"),
+            ("deploy-production", "2024-01-01T00:00:00+00:00"),
+            ("core@latest", "2024-01-01T00:00:00+00:00"),
+        ]
+        .into_iter()
+        .map(|(tag, date)| (tag.to_string(), date.to_string()))
+        .collect();
+
+        let releases = releases_from_tags(tags);
+
+        let tag_names: Vec<&str> = releases.iter().map(|r| r.tag_name.as_str()).collect();
+        assert_eq!(tag_names, vec!["core@v1.1.0-rc.0", "core@v1.0.0", "v2.0.0"]);
+        assert!(releases[0].is_prerelease);
+        assert_eq!(releases[1].published_at, "2024-01-01T00:00:00+00:00");
+    }
+}
@@ -1,13 +1,21 @@
 use colorize::AnsiColor;
 use inquire::{
+    list_option::ListOption,
     ui::{Color, RenderConfig, Styled},
-    Confirm, Editor, Select,
+    validator::Validation,
+    Confirm, Editor, MultiSelect, Select,
 };
 use semver::Version;
 
 use crate::{
     ctx::AppContext,
-    transform::{PreReleaseType, PreReleaseVersionBump, VersionBump},
+    error::{RemaError, Result},
+    read::{LockfileMismatch, LockfileMismatchResolution},
+    transform::{
+        self, AuditFinding, AuditIssue, PkgStatus, PreReleaseType, PreReleaseVersionBump,
+        PublishedRelease, ReleaseReport, VersionBump,
+    },
+    OutputFormat,
 };
 
 const MAJOR: &str = "major";
@@ -20,10 +28,262 @@ const PRE_NEW: &str = "create new pre-release";
 const ALPHA: &str = "alpha";
 const BETA: &str = "beta";
 const RC: &str = "rc";
+const RESYNC: &str = "resync lockfile to package.json version";
+const SKIP: &str = "skip package";
+const ALL_CHANGED: &str = "all changed packages";
+
+/// Select the packages to release from the labels of `AppContext::get_latest_pkg_names`. When
+/// packages have changed since their last release, a shortcut to select all of them is offered.
+pub fn select_pkg_names(options: Vec<String>, changed_pkgs: &[String]) -> Result<Vec<String>> {
+    let mut ordered_options = order_pkg_names(options);
+    let all_changed = format!("{} ({})", ALL_CHANGED, changed_pkgs.join(", "));
+    if !changed_pkgs.is_empty() {
+        ordered_options.insert(0, all_changed.clone());
+    }
+
+    let ans = MultiSelect::new("Select which packages to release", ordered_options)
+        .with_validator(|selected: &[ListOption<&String>]| {
+            if selected.is_empty() {
+                Ok(Validation::Invalid("Select at least one package".into()))
+            } else {
+                Ok(Validation::Valid)
+            }
+        })
+        .prompt()?;
+
+    let mut pkg_names: Vec<String> = Vec::new();
+    for label in ans {
+        let names = if label == all_changed {
+            changed_pkgs.to_vec()
+        } else {
+            vec![pkg_name_from_label(&label)]
+        };
+        for name in names {
+            if !pkg_names.contains(&name) {
+                pkg_names.push(name);
+            }
+        }
+    }
+
+    Ok(pkg_names)
+}
+
+/// Strip the hint in parentheses from a package label, e.g. `megatron (2 commits, ...)`
+fn pkg_name_from_label(label: &str) -> String {
+    label
+        .split_once(" (")
+        .map_or(label, |(name, _)| name)
+        .trim()
+        .to_string()
+}
+
+/// Print each package with its released and local versions and the changes since its latest
+/// release
+pub fn print_status(statuses: &[PkgStatus]) {
+    let name_width = statuses
+        .iter()
+        .map(|status| status.name.len())
+        .max()
+        .unwrap_or(0)
+        .max(7);
+
+    println!(
+        "  {:<name_width$}  {:<12}  {:<16}  {:<16}  {:<10}  {:>7}  {:>13}",
+        "PACKAGE", "STABLE", "PRE-RELEASE", "LOCAL", "RELEASED", "BEHIND", "FILES CHANGED"
+    );
+
+    for status in statuses {
+        let released = if status.local_only {
+            "local only".to_string()
+        } else {
+            // Only the date of e.g. `2024-12-15T15:44:20Z`
+            status
+                .last_released_at
+                .as_ref()
+                .and_then(|published_at| published_at.get(..10))
+                .unwrap_or("-")
+                .to_string()
+        };
+        let or_dash = |value: &Option<String>| value.clone().unwrap_or("-".to_string());
+        let or_unknown = |value: Option<usize>| value.map_or("?".to_string(), |v| v.to_string());
+
+        let line = format!(
+            "  {:<name_width$}  {:<12}  {:<16}  {:<16}  {:<10}  {:>7}  {:>13}",
+            status.name,
+            or_dash(&status.latest_stable),
+            or_dash(&status.latest_pre),
+            or_dash(&status.local_version),
+            released,
+            or_unknown(status.commits_behind),
+            or_unknown(status.files_changed)
+        );
+        if status.commits_behind == Some(0) {
+            println!("{}", line.grey());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+pub fn print_audit(findings: &[AuditFinding]) {
+    if findings.is_empty() {
+        println!(
+            "{}",
+            "Tags, releases and package manifests are consistent".green()
+        );
+        return;
+    }
+
+    let tag_width = findings
+        .iter()
+        .map(|finding| finding.tag.len())
+        .max()
+        .unwrap_or(0)
+        .max(3);
+
+    println!("  {:<20}  {:<tag_width$}  DETAIL", "ISSUE", "TAG");
+
+    for finding in findings {
+        let issue = match finding.issue {
+            AuditIssue::TagWithoutRelease => "tag without release",
+            AuditIssue::MissingLocalTag => "missing local tag",
+            AuditIssue::MissingRemoteTag => "missing remote tag",
+            AuditIssue::DraftRelease => "draft release",
+            AuditIssue::PrereleaseMismatch => "pre-release mismatch",
+            AuditIssue::ManifestMismatch => "manifest mismatch",
+        };
+        println!(
+            "  {:<20}  {:<tag_width$}  {}",
+            issue, finding.tag, finding.detail
+        );
+    }
+}
+
+/// Print a warning on stderr, leaving stdout to the results
+pub fn print_warning(msg: &str) {
+    eprintln!("{}", msg.to_string().yellow());
+}
+
+/// Print a human oriented message, on stderr when stdout is reserved for JSON
+pub fn print_message(ctx: &AppContext, msg: &str) {
+    match ctx.get_output_format() {
+        OutputFormat::Table => println!("{}", msg),
+        OutputFormat::Json => eprintln!("{}", msg),
+    }
+}
+
+/// Print the planned or completed releases
+pub fn print_release_report(report: &ReleaseReport) {
+    if report.dry_run {
+        println!("Dry run, nothing was written or released");
+    } else if report.draft {
+        println!("Draft releases created. Publish them with `rema publish <tag>`");
+    } else {
+        println!("Release completed successfully!");
+    }
 
-pub fn select_pkg_name(options: Vec<String>) -> Result<String, Box<dyn std::error::Error>> {
-    let ordered_options = order_pkg_names(options);
-    let ans = Select::new("Select which package to manage", ordered_options).prompt()?;
+    for release in &report.releases {
+        let latest = match release.latest {
+            Some(true) => " (latest)",
+            Some(false) => " (not latest)",
+            None => "",
+        };
+        println!(
+            "  {} version for {} from {} to {}{}",
+            if report.dry_run {
+                "Would bump"
+            } else {
+                "Bumped"
+            },
+            release.package,
+            release.previous_version,
+            release.new_version,
+            latest
+        );
+        if let Some(url) = &release.release_url {
+            println!("    {}", url.clone().cyan());
+        }
+        for url in &release.asset_urls {
+            println!("    {}", url.clone().cyan());
+        }
+    }
+
+    for path in &report.files_changed {
+        println!(
+            "  {} {}",
+            if report.dry_run {
+                "Would update"
+            } else {
+                "Updated"
+            },
+            path
+        );
+    }
+}
+
+pub fn print_published_release(published: &PublishedRelease) {
+    println!("Published {}", published.tag);
+    if let Some(url) = &published.release_url {
+        println!("    {}", url.clone().cyan());
+    }
+}
+
+/// Print a value as pretty JSON on stdout
+pub fn print_json<T: serde::Serialize + ?Sized>(value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| RemaError::InvalidInput(format!("Failed to serialize output: {}", e)))?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub fn print_lockfile_diagnostics(mismatches: &[LockfileMismatch]) {
+    eprintln!("{}", "  Lockfile diagnostics".yellow());
+    for mismatch in mismatches {
+        eprintln!(
+            "  {} {} records version {}, but {} has {}",
+            mismatch
+                .pkg_name
+                .clone()
+                .unwrap_or("<unnamed>".to_string())
+                .cyan(),
+            mismatch.package_lock_json.path.clone().unwrap_or_default(),
+            mismatch
+                .package_lock_json
+                .version
+                .clone()
+                .unwrap_or("none".to_string())
+                .red(),
+            mismatch.package_json.path.clone().unwrap_or_default(),
+            mismatch
+                .package_json
+                .version
+                .clone()
+                .unwrap_or("none".to_string())
+                .green(),
+        );
+    }
+}
+
+pub fn select_lockfile_mismatch_resolution(
+    mismatch: &LockfileMismatch,
+) -> Result<LockfileMismatchResolution> {
+    // Without a version in package.json there is nothing to resync to
+    let options = if mismatch.package_json.version.is_some() {
+        vec![RESYNC, SKIP]
+    } else {
+        vec![SKIP]
+    };
+
+    let msg = format!(
+        "How to handle the lockfile of {}?",
+        mismatch.pkg_name.clone().unwrap_or_default()
+    );
+    let ans = Select::new(msg.as_str(), options).prompt()?;
+    let ans = match ans {
+        RESYNC => LockfileMismatchResolution::Resync,
+        SKIP => LockfileMismatchResolution::Skip,
+        _ => unreachable!("Invalid lockfile resolution"),
+    };
     Ok(ans)
 }
 
@@ -33,12 +293,16 @@ fn order_pkg_names(pkgs: Vec<String>) -> Vec<String> {
     pkgs
 }
 
-pub fn select_version_bump(ctx: &AppContext) -> Result<VersionBump, Box<dyn std::error::Error>> {
+pub fn select_version_bump(ctx: &AppContext) -> Result<VersionBump> {
     let releases = ctx.get_latest_versions();
-    let selected_pkg = ctx.get_selected_package().unwrap();
-    let selected_pkg_release_info = releases.get(selected_pkg).unwrap();
+    let selected_pkg = ctx
+        .get_selected_package()
+        .ok_or_else(|| RemaError::InvalidInput("No package selected".to_string()))?;
+    let selected_pkg_release_info = releases
+        .get(selected_pkg)
+        .ok_or_else(|| RemaError::InvalidInput(format!("Unknown package: {}", selected_pkg)))?;
 
-    let existing_version = selected_pkg_release_info.version.clone();
+    let existing_version = transform::get_current_version(ctx, selected_pkg)?;
 
     let mut options = if existing_version.pre.is_empty() {
         vec![MAJOR, MINOR, PATCH, PRE_NEW]
@@ -58,16 +322,13 @@ pub fn select_version_bump(ctx: &AppContext) -> Result<VersionBump, Box<dyn std:
         PRE => VersionBump::Pre,
         PRE_NEW => create_pre_release(ctx, existing_version)?,
         USE_LOCAL => VersionBump::RetainIfUnreleased,
-        _ => panic!("Invalid version bump"),
+        _ => unreachable!("Invalid version bump"),
     };
 
     Ok(ans)
 }
 
-fn create_pre_release(
-    ctx: &AppContext,
-    existing_version: Version,
-) -> Result<VersionBump, Box<dyn std::error::Error>> {
+fn create_pre_release(ctx: &AppContext, existing_version: Version) -> Result<VersionBump> {
     let pre_base_version = select_pre_release_base_version()?;
     let pre_type = select_pre_release_type(ctx, &pre_base_version, existing_version)?;
     Ok(VersionBump::PreNew(pre_type, pre_base_version))
@@ -77,7 +338,7 @@ fn select_pre_release_type(
     ctx: &AppContext,
     pre_base_version: &PreReleaseVersionBump,
     existing_version: Version,
-) -> Result<PreReleaseType, Box<dyn std::error::Error>> {
+) -> Result<PreReleaseType> {
     let mut options = vec![];
 
     match pre_base_version {
@@ -86,15 +347,15 @@ fn select_pre_release_type(
         // are created that are of lower version than the existing ones.
         PreReleaseVersionBump::Retain => {
             let alpha = ctx.get_pre_for_version_for_selected_pkg(ALPHA, &existing_version);
-            if !alpha.is_some() {
+            if alpha.is_none() {
                 options.push(ALPHA);
             }
             let beta = ctx.get_pre_for_version_for_selected_pkg(BETA, &existing_version);
-            if !beta.is_some() {
+            if beta.is_none() {
                 options.push(BETA);
             }
             let rc = ctx.get_pre_for_version_for_selected_pkg(RC, &existing_version);
-            if !rc.is_some() {
+            if rc.is_none() {
                 options.push(RC);
             }
         }
@@ -106,7 +367,7 @@ fn select_pre_release_type(
     }
 
     if options.is_empty() {
-        return Err("No new pre-release types available for this version. Try bumping existing ones or bump the major, minor or patch".into());
+        return Err(RemaError::BadTag("No new pre-release types available for this version. Try bumping existing ones or bump the major, minor or patch".to_string()));
     }
 
     let ans = Select::new("Select which pre-release type to create", options).prompt()?;
@@ -114,12 +375,12 @@ fn select_pre_release_type(
         ALPHA => PreReleaseType::Alpha,
         BETA => PreReleaseType::Beta,
         RC => PreReleaseType::Rc,
-        _ => panic!("Invalid pre-release type"),
+        _ => unreachable!("Invalid pre-release type"),
     };
     Ok(ans)
 }
 
-fn select_pre_release_base_version() -> Result<PreReleaseVersionBump, Box<dyn std::error::Error>> {
+fn select_pre_release_base_version() -> Result<PreReleaseVersionBump> {
     let options_pre_versions = vec![MAJOR, MINOR, PATCH, RETAIN];
     let ans = Select::new(
         "Which version number to bump for this pre-release?",
@@ -131,12 +392,12 @@ fn select_pre_release_base_version() -> Result<PreReleaseVersionBump, Box<dyn st
         MINOR => PreReleaseVersionBump::Minor,
         PATCH => PreReleaseVersionBump::Patch,
         RETAIN => PreReleaseVersionBump::Retain,
-        _ => panic!("Invalid base version"),
+        _ => unreachable!("Invalid base version"),
     };
     Ok(ans)
 }
 
-pub fn input_release_title(initial_title: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub fn input_release_title(initial_title: &str) -> Result<String> {
     let format_title = format!(
         "Title (currently set to: {}):",
         initial_title.to_string().yellow()
@@ -147,7 +408,7 @@ pub fn input_release_title(initial_title: &str) -> Result<String, Box<dyn std::e
     Ok(title)
 }
 
-pub fn input_release_description(ctx: &AppContext) -> Result<String, Box<dyn std::error::Error>> {
+pub fn input_release_description(ctx: &AppContext) -> Result<String> {
     let gh_msg = if ctx.get_gh_generate_release_notes() {
         "(gh auto generate will not overwrite title or description)"
     } else {
@@ -172,17 +433,59 @@ pub fn input_release_description(ctx: &AppContext) -> Result<String, Box<dyn std
     Ok(description)
 }
 
-pub fn input_confirmation(ctx: &AppContext) -> Result<bool, Box<dyn std::error::Error>> {
-    let selected_pkg = ctx.get_selected_package().unwrap().clone();
-    let target_release = ctx.get_target_release_info().unwrap().clone();
+pub fn input_release_dependents(dependents: &[String]) -> Result<bool> {
+    let msg = format!(
+        "Also patch release the packages that depend on it ({})?",
+        dependents.join(", ").cyan()
+    );
+    let ans = Confirm::new(msg.as_str())
+        .with_default(false)
+        .with_help_message("Their dependency ranges are updated either way")
+        .prompt()?;
 
-    let help_msg = get_confirmation_help_msg(ctx);
+    Ok(ans)
+}
 
+pub fn input_publish_draft(tag: &str) -> Result<bool> {
     let msg = format!(
-        "Are you sure you want to release {} version {}?",
-        selected_pkg.to_string().cyan(),
-        target_release.version.to_string().green()
+        "A draft release of {} exists. Publish it instead of creating a new release?",
+        tag.to_string().cyan()
     );
+    let ans = Confirm::new(msg.as_str())
+        .with_default(true)
+        .with_help_message("The draft keeps its release notes")
+        .prompt()?;
+
+    Ok(ans)
+}
+
+pub fn input_confirmation(ctx: &AppContext) -> Result<bool> {
+    let targets = ctx
+        .get_release_targets()
+        .iter()
+        .map(|target| {
+            let draft_msg = if target.publish_draft {
+                " (publishing its draft)"
+            } else {
+                ""
+            };
+            format!(
+                "{} version {}{}",
+                target.pkg_name.to_string().cyan(),
+                target.release_info.version.to_string().green(),
+                draft_msg
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let help_msg = get_confirmation_help_msg(ctx);
+
+    let msg = if ctx.is_draft() {
+        format!("Are you sure you want to draft releases of {}?", targets)
+    } else {
+        format!("Are you sure you want to release {}?", targets)
+    };
     let ans = Confirm::new(msg.as_str())
         .with_default(true)
         .with_help_message(help_msg.as_str())
@@ -192,26 +495,51 @@ pub fn input_confirmation(ctx: &AppContext) -> Result<bool, Box<dyn std::error::
 }
 
 fn get_confirmation_help_msg(ctx: &AppContext) -> String {
-    let target_release = ctx.get_target_release_info().unwrap().clone();
+    let mut lines: Vec<String> = Vec::new();
+
+    for target in ctx.get_release_targets() {
+        let dependents: Vec<String> = ctx
+            .get_dependency_graph()
+            .get_dependents(&target.pkg_name)
+            .into_iter()
+            .filter(|dependent| !ctx.is_release_target(dependent))
+            .collect();
+        if !dependents.is_empty() {
+            lines.push(format!(
+                "Will update dependency ranges in {}",
+                dependents.join(", ")
+            ));
+        }
 
-    let mut help_msg = String::new();
+        let local_pkg_files = match &target.release_info.local_pkg_files {
+            None => continue,
+            Some(files) => files.clone(),
+        };
 
-    let local_pkg_files = match target_release.local_pkg_files {
-        None => return help_msg,
-        Some(files) => files,
-    };
+        if let Some(path) = local_pkg_files
+            .package_json
+            .and_then(|pkg| pkg.name.and(pkg.path))
+        {
+            lines.push(format!("Will update {}", path))
+        }
 
-    local_pkg_files
-        .package_json
-        .and_then(|pkg| pkg.name.and_then(|_| pkg.path))
-        .map(|path| help_msg.push_str(&format!("Will update {}", path)));
+        if let Some(path) = local_pkg_files
+            .package_lock_json
+            .and_then(|pkg| pkg.name.and(pkg.path))
+        {
+            lines.push(format!("Will update {}", path))
+        }
 
-    local_pkg_files
-        .package_lock_json
-        .and_then(|pkg| pkg.name.and_then(|_| pkg.path))
-        .map(|path| help_msg.push_str(&format!("\nWill update {}", path)));
+        if let Some(path) = local_pkg_files
+            .workspace_package_lock_json
+            .and_then(|pkg| pkg.name.and(pkg.path))
+        {
+            lines.push(format!("Will update {}", path))
+        }
+    }
 
-    help_msg
+    lines.dedup();
+    lines.join("\n")
 }
 
 fn description_render_config() -> RenderConfig<'static> {
@@ -221,10 +549,20 @@ fn description_render_config() -> RenderConfig<'static> {
 
 #[cfg(test)]
 mod tests {
-    use crate::gh::Release;
+    use crate::forge::Release;
 
     use super::*;
 
+    #[test]
+    fn should_strip_hint_from_pkg_label() {
+        assert_eq!(pkg_name_from_label("megatron"), "megatron");
+        assert_eq!(pkg_name_from_label("megatron (unreleased)"), "megatron");
+        assert_eq!(
+            pkg_name_from_label("@acme/ui (3 commits, 5 files changed)"),
+            "@acme/ui"
+        );
+    }
+
     #[test]
     fn should_error_when_no_options_are_available_for_select_pre_release_type() {
         let test_releases = vec![
@@ -246,7 +584,7 @@ mod tests {
             },
         ];
 
-        let mut ctx = AppContext::new(test_releases);
+        let mut ctx = AppContext::new(test_releases).unwrap();
         ctx.set_selected_package("elephant".to_string());
 
         let existing_version = Version::new(1, 0, 1);
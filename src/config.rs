@@ -0,0 +1,288 @@
+use std::{collections::HashMap, env, fs};
+
+use serde::Deserialize;
+
+use crate::{
+    api::forge::ForgeKind,
+    error::{RemaError, Result},
+};
+
+/// Name of the optional config file in the directory rema is run from
+pub const CONFIG_FILE_NAME: &str = "rema.json";
+
+/// Settings read from `rema.json`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+    /// Packages that always share a version, e.g. `[["core", "react", "vue"]]`
+    #[serde(default)]
+    pub version_groups: Vec<Vec<String>>,
+    /// Where releases are hosted, `github`, `gitlab`, `gitea` (also for Forgejo) or `tags` for
+    /// plain git tags. Detected from the `origin` remote when not set.
+    #[serde(default)]
+    pub forge: Option<ForgeKind>,
+    /// Base URL of a Gitea or Forgejo instance, e.g. `https://git.example.org`. Derived from the
+    /// `origin` remote when not set.
+    #[serde(default)]
+    pub forge_url: Option<String>,
+    /// Count draft releases as released versions, e.g. for the latest version of a package
+    #[serde(default)]
+    pub include_drafts: bool,
+    /// Which releases get the Latest badge on GitHub
+    #[serde(default)]
+    pub latest: LatestConfig,
+    /// Files to upload with the releases of a package, as globs relative to the package
+    /// directory, e.g. `{ "core": ["dist/*.tgz", { "glob": "dist/*.map", "optional": true }] }`
+    #[serde(default)]
+    pub assets: HashMap<String, Vec<AssetPattern>>,
+    /// Add a table with the SHA-256 checksums of the assets to the release notes. A
+    /// `SHA256SUMS` file is uploaded with the assets either way.
+    #[serde(default)]
+    pub checksum_table: bool,
+    /// Upload a tarball of the package directory at the release commit, e.g.
+    /// `scope-core-v1.2.0.tar.gz` for `@scope/core@v1.2.0`
+    #[serde(default)]
+    pub source_archive: bool,
+    /// Update the root lockfile with its package manager, e.g. `npm install
+    /// --package-lock-only`, instead of editing package-lock.json files in place. Keeps integrity
+    /// fields valid when dependency ranges change. Yarn 1 has no lockfile only install, so it runs
+    /// `yarn install --ignore-scripts`, which also updates `node_modules`.
+    #[serde(default)]
+    pub regenerate_lockfiles: bool,
+    /// Shell commands to run during a release, e.g. `{ "postWrite": ["npm run build"] }`
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// What to sign, e.g. `{ "commits": true, "tags": true, "checksums": true }`
+    #[serde(default)]
+    pub signing: SigningConfig,
+}
+
+/// Shell commands run at a step of the release, with the releases in `REMA_*` environment
+/// variables. A failing command aborts the release and rolls it back.
+///
+/// Package hooks run once for every released package, with `REMA_PACKAGE`, `REMA_PACKAGE_DIR`,
+/// `REMA_PREVIOUS_VERSION`, `REMA_VERSION` and `REMA_TAG`. Hooks on the working tree run once, with
+/// the space separated `REMA_PACKAGES`, `REMA_PREVIOUS_VERSIONS`, `REMA_VERSIONS` and `REMA_TAGS`.
+///
+/// A rollback reverts the release commit, which includes what the hooks changed. When the release
+/// fails before its commit, only the package files rema wrote are restored: files the hooks
+/// created or changed stay in the working tree for `git status` to show.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Before anything is written, per package
+    #[serde(default)]
+    pub pre_bump: Vec<String>,
+    /// After the new versions are written, e.g. to regenerate lockfiles or build, on the tree
+    #[serde(default)]
+    pub post_write: Vec<String>,
+    /// Right before the release commit, which includes files changed by hooks, on the tree
+    #[serde(default)]
+    pub pre_commit: Vec<String>,
+    /// After every release is created, per package
+    #[serde(default)]
+    pub post_release: Vec<String>,
+}
+
+/// Signing of release commits and tags with git, SSH or GPG as set by `gpg.format`, and of the
+/// `SHA256SUMS` asset with `ssh-keygen -Y sign`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SigningConfig {
+    /// Sign release commits, `git commit -S`
+    #[serde(default)]
+    pub commits: bool,
+    /// Sign release tags, `git tag -s`. Rema then creates the tags itself instead of the forge.
+    #[serde(default)]
+    pub tags: bool,
+    /// Upload a `SHA256SUMS.sig` signature, which needs an SSH key
+    #[serde(default)]
+    pub checksums: bool,
+    /// Key to sign with, in the format of git's `user.signingKey`, which is used when not set
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+impl SigningConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.commits || self.tags || self.checksums
+    }
+}
+
+/// A glob of release assets. A required glob fails the release when it matches no file.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(from = "AssetPatternField")]
+pub struct AssetPattern {
+    pub glob: String,
+    pub optional: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AssetPatternField {
+    Glob(String),
+    #[serde(rename_all = "camelCase")]
+    Detailed {
+        glob: String,
+        #[serde(default)]
+        optional: bool,
+    },
+}
+
+impl From<AssetPatternField> for AssetPattern {
+    fn from(field: AssetPatternField) -> Self {
+        match field {
+            AssetPatternField::Glob(glob) => Self {
+                glob,
+                optional: false,
+            },
+            AssetPatternField::Detailed { glob, optional } => Self { glob, optional },
+        }
+    }
+}
+
+/// Rules for the Latest badge, e.g. `{ "packages": ["core"], "backports": false }`. Without
+/// rules, the forge decides.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LatestConfig {
+    /// Only releases of these packages may be latest, any package when empty
+    #[serde(default)]
+    pub packages: Vec<String>,
+    /// Whether a release below the highest released version of its package, e.g. a patch of an
+    /// older major line, may be latest
+    #[serde(default = "default_true")]
+    pub backports: bool,
+}
+
+impl Default for LatestConfig {
+    fn default() -> Self {
+        Self {
+            packages: Vec::new(),
+            backports: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Config {
+    /// The version group a package belongs to, if any
+    pub fn get_version_group(&self, pkg_name: &str) -> Option<&Vec<String>> {
+        self.version_groups
+            .iter()
+            .find(|group| group.iter().any(|member| member == pkg_name))
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut seen: Vec<&String> = Vec::new();
+        for member in self.version_groups.iter().flatten() {
+            if seen.contains(&member) {
+                return Err(RemaError::InvalidInput(format!(
+                    "{} is in more than one version group",
+                    member
+                )));
+            }
+            seen.push(member);
+        }
+
+        Ok(())
+    }
+}
+
+/// Load `rema.json` from the current directory. A missing file means default settings.
+pub fn load_config() -> Result<Config> {
+    let path = env::current_dir()
+        .map_err(|e| RemaError::Manifest(format!("Failed to get current directory: {}", e)))?
+        .join(CONFIG_FILE_NAME);
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => {
+            return Err(RemaError::Manifest(format!(
+                "Failed to read {}: {}",
+                CONFIG_FILE_NAME, e
+            )))
+        }
+    };
+
+    parse_config(&content)
+}
+
+fn parse_config(content: &str) -> Result<Config> {
+    let config: Config = serde_json::from_str(content).map_err(|e| {
+        RemaError::InvalidInput(format!("Failed to parse {}: {}", CONFIG_FILE_NAME, e))
+    })?;
+    config.validate()?;
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_version_groups() {
+        let config = parse_config(r#"{ "versionGroups": [["core", "react", "vue"]] }"#).unwrap();
+
+        assert_eq!(
+            config.get_version_group("react"),
+            Some(&vec![
+                "core".to_string(),
+                "react".to_string(),
+                "vue".to_string()
+            ])
+        );
+        assert_eq!(config.get_version_group("docs"), None);
+    }
+
+    #[test]
+    fn should_reject_package_in_multiple_version_groups() {
+        let result = parse_config(r#"{ "versionGroups": [["core", "react"], ["react", "vue"]] }"#);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_default_to_letting_the_forge_pick_latest() {
+        let config = parse_config(r#"{ "latest": { "packages": ["core"] } }"#).unwrap();
+
+        assert_eq!(config.latest.packages, vec!["core".to_string()]);
+        assert!(config.latest.backports);
+        assert!(Config::default().latest.backports);
+    }
+
+    #[test]
+    fn should_parse_required_and_optional_assets() {
+        let config = parse_config(
+            r#"{ "assets": { "core": ["dist/*.tgz", { "glob": "dist/*.map", "optional": true }] } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.assets["core"],
+            vec![
+                AssetPattern {
+                    glob: "dist/*.tgz".to_string(),
+                    optional: false
+                },
+                AssetPattern {
+                    glob: "dist/*.map".to_string(),
+                    optional: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_forge() {
+        let config = parse_config(r#"{ "forge": "gitlab" }"#).unwrap();
+
+        assert_eq!(config.forge, Some(ForgeKind::GitLab));
+        assert!(parse_config(r#"{ "forge": "bitbucket" }"#).is_err());
+    }
+}
@@ -3,43 +3,140 @@ use std::collections::HashMap;
 use semver::Version;
 
 use crate::{
-    gh::{self, Release},
-    read::LocalPackageFiles,
-    transform::{self, PreReleaseType, ReleaseInfo, VersionBump},
+    config::{self, Config},
+    error::Result,
+    forge::Release,
+    git::ChangeSummary,
+    io::Io,
+    read::{LocalPackageFiles, LockfileMismatch},
+    transform::{
+        self, AssetGlob, DependencyGraph, LatestOverride, PreReleaseType, ReleaseInfo,
+        ReleaseTarget, VersionBump,
+    },
+    OutputFormat,
 };
 
+/// Everything rema knows about the repository and the releases planned in this run
 pub struct AppContext {
+    gh_releases: Vec<Release>,
     local_pkg_files: Option<Vec<LocalPackageFiles>>,
+    private_pkg_files: Vec<LocalPackageFiles>,
     all_gh_versions: HashMap<String, Vec<ReleaseInfo>>,
     latest_gh_versions: HashMap<String, ReleaseInfo>,
     selected_pkg: Option<String>,
     selected_bump: Option<VersionBump>,
     target_version: Option<ReleaseInfo>,
+    release_targets: Vec<ReleaseTarget>,
+    pkg_changes: HashMap<String, ChangeSummary>,
+    lockfile_resyncs: Vec<LockfileMismatch>,
+    config: Config,
+    output: OutputFormat,
+    draft: bool,
+    latest_overrides: Vec<LatestOverride>,
+    asset_globs: Vec<AssetGlob>,
     gh_generate_release_notes: bool,
 }
 
-pub fn create_ctx_with_data() -> AppContext {
-    let releases = gh::list_releases().unwrap_or_else(|e| {
-        panic!("Failed to list releases {:?}", Some(e));
-    });
+pub fn create_ctx_with_data(io: &dyn Io) -> Result<AppContext> {
+    let releases = io.list_releases()?;
+    let config = config::load_config()?;
 
-    AppContext::new(releases)
+    AppContext::with_config(releases, config)
 }
 
 impl AppContext {
-    pub fn new(releases: Vec<Release>) -> Self {
-        let all_versions = transform::extract_all_gh_pkgs_and_versions(releases.clone());
+    pub fn new(releases: Vec<Release>) -> Result<Self> {
+        Self::with_config(releases, Config::default())
+    }
+
+    /// Drafts only count as released versions when the config includes them
+    pub fn with_config(releases: Vec<Release>, config: Config) -> Result<Self> {
+        let released: Vec<Release> = releases
+            .iter()
+            .filter(|release| config.include_drafts || !release.is_draft)
+            .cloned()
+            .collect();
+        let all_versions = transform::extract_all_gh_pkgs_and_versions(released)?;
         let latest_versions = transform::extract_latest_gh_pkgs_and_versions(&all_versions);
 
-        Self {
+        Ok(Self {
+            gh_releases: releases,
             all_gh_versions: all_versions,
             latest_gh_versions: latest_versions,
             local_pkg_files: None,
+            private_pkg_files: Vec::new(),
             selected_pkg: None,
             selected_bump: None,
             target_version: None,
+            release_targets: Vec::new(),
+            pkg_changes: HashMap::new(),
+            lockfile_resyncs: Vec::new(),
+            config,
+            output: OutputFormat::default(),
+            draft: false,
+            latest_overrides: Vec::new(),
+            asset_globs: Vec::new(),
             gh_generate_release_notes: true,
-        }
+        })
+    }
+
+    pub fn get_output_format(&self) -> OutputFormat {
+        self.output
+    }
+
+    pub fn set_output_format(&mut self, output: OutputFormat) {
+        self.output = output;
+    }
+
+    /// Whether releases are created as drafts, to publish later
+    pub fn is_draft(&self) -> bool {
+        self.draft
+    }
+
+    pub fn set_draft(&mut self, draft: bool) {
+        self.draft = draft;
+    }
+
+    /// `--latest` flags of this run
+    pub fn get_latest_overrides(&self) -> &[LatestOverride] {
+        &self.latest_overrides
+    }
+
+    pub fn set_latest_overrides(&mut self, overrides: Vec<LatestOverride>) {
+        self.latest_overrides = overrides;
+    }
+
+    /// `--asset` flags of this run
+    pub fn get_asset_globs(&self) -> &[AssetGlob] {
+        &self.asset_globs
+    }
+
+    pub fn set_asset_globs(&mut self, globs: Vec<AssetGlob>) {
+        self.asset_globs = globs;
+    }
+
+    pub fn get_config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// Other members of the version group of a package that are known to rema
+    pub fn get_version_group_members(&self, pkg_name: &str) -> Vec<String> {
+        self.config
+            .get_version_group(pkg_name)
+            .map(|group| {
+                group
+                    .iter()
+                    .filter(|member| {
+                        *member != pkg_name && self.latest_gh_versions.contains_key(*member)
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     pub fn get_gh_generate_release_notes(&self) -> bool {
@@ -51,22 +148,14 @@ impl AppContext {
         pre: &str,
         version: &Version,
     ) -> Option<&ReleaseInfo> {
-        let pkg_name = self.get_selected_package();
+        let pkg_name = self.get_selected_package()?;
 
-        if pkg_name.is_none() {
-            panic!("No package selected");
-        }
-
-        self.all_gh_versions
-            .get(pkg_name.unwrap())
-            .unwrap()
-            .iter()
-            .find(|rel| {
-                rel.version.major == version.major
-                    && rel.version.minor == version.minor
-                    && rel.version.patch == version.patch
-                    && rel.version.pre.as_str().contains(pre)
-            })
+        self.all_gh_versions.get(pkg_name)?.iter().find(|rel| {
+            rel.version.major == version.major
+                && rel.version.minor == version.minor
+                && rel.version.patch == version.patch
+                && rel.version.pre.as_str().contains(pre)
+        })
     }
 
     pub fn get_selected_package(&self) -> Option<&String> {
@@ -77,12 +166,15 @@ impl AppContext {
         self.target_version.as_ref()
     }
 
+    /// Package names for display, with a hint about unreleased changes when they are loaded
     pub fn get_latest_pkg_names(&self) -> Vec<String> {
         self.latest_gh_versions
             .iter()
             .map(|(name, info)| {
                 if info.local_only {
                     format!("{} (unreleased)", name)
+                } else if let Some(changes) = self.pkg_changes.get(name) {
+                    format!("{} ({})", name, transform::describe_changes(changes))
                 } else {
                     name.clone()
                 }
@@ -90,6 +182,107 @@ impl AppContext {
             .collect()
     }
 
+    /// Look up the commits and files changed since the latest release of every package, scoped
+    /// to the package directory. Packages whose last tag isn't available locally are left out.
+    pub fn load_pkg_changes(&mut self, io: &dyn Io) {
+        for (name, info) in self.latest_gh_versions.iter() {
+            let tag = if info.local_only {
+                None
+            } else {
+                Some(transform::create_pkg_release_title(name, info))
+            };
+            let dir = info
+                .local_pkg_files
+                .as_ref()
+                .and_then(|files| files.dir.clone())
+                .unwrap_or(".".to_string());
+
+            if let Ok(changes) = io.get_changes_since(tag.as_deref(), &dir) {
+                self.pkg_changes.insert(name.clone(), changes);
+            }
+        }
+    }
+
+    pub fn get_pkg_changes(&self, pkg_name: &str) -> Option<&ChangeSummary> {
+        self.pkg_changes.get(pkg_name)
+    }
+
+    /// Packages that are unreleased or have commits since their latest release
+    pub fn get_changed_pkg_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .latest_gh_versions
+            .iter()
+            .filter(|(name, info)| {
+                info.local_only
+                    || self
+                        .pkg_changes
+                        .get(name.as_str())
+                        .is_some_and(|changes| changes.commits > 0)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub fn get_local_pkg_files(&self) -> &[LocalPackageFiles] {
+        self.local_pkg_files.as_deref().unwrap_or_default()
+    }
+
+    /// Lockfiles to set to their package.json version, written with the release
+    pub fn get_lockfile_resyncs(&self) -> &[LockfileMismatch] {
+        &self.lockfile_resyncs
+    }
+
+    pub fn add_lockfile_resync(&mut self, mismatch: LockfileMismatch) {
+        self.lockfile_resyncs.push(mismatch);
+    }
+
+    /// Private workspace packages, never released but with dependency ranges to update
+    pub fn get_private_pkg_files(&self) -> &[LocalPackageFiles] {
+        &self.private_pkg_files
+    }
+
+    pub fn set_private_pkg_files(&mut self, private_pkg_files: Vec<LocalPackageFiles>) {
+        self.private_pkg_files = private_pkg_files;
+    }
+
+    pub fn get_dependency_graph(&self) -> DependencyGraph {
+        let pkgs: Vec<LocalPackageFiles> = self
+            .get_local_pkg_files()
+            .iter()
+            .chain(self.get_private_pkg_files())
+            .cloned()
+            .collect();
+        DependencyGraph::new(&pkgs)
+    }
+
+    /// Every package release planned in this run, released together in one transaction
+    pub fn get_release_targets(&self) -> &[ReleaseTarget] {
+        &self.release_targets
+    }
+
+    pub fn is_release_target(&self, pkg_name: &str) -> bool {
+        self.release_targets
+            .iter()
+            .any(|target| target.pkg_name == pkg_name)
+    }
+
+    pub fn get_releases(&self) -> &[Release] {
+        &self.gh_releases
+    }
+
+    /// The unpublished draft release of a tag, if any
+    pub fn find_draft_release(&self, tag: &str) -> Option<&Release> {
+        self.gh_releases
+            .iter()
+            .find(|release| release.is_draft && release.tag_name == tag)
+    }
+
+    pub fn get_all_versions(&self) -> &HashMap<String, Vec<ReleaseInfo>> {
+        &self.all_gh_versions
+    }
+
     pub fn get_latest_versions(&self) -> &HashMap<String, ReleaseInfo> {
         &self.latest_gh_versions
     }
@@ -98,23 +291,23 @@ impl AppContext {
         self.selected_pkg = Some(pkg_name);
     }
 
-    pub fn set_and_match_local_pkg_files(&mut self, local_pkg_files: Vec<LocalPackageFiles>) {
+    pub fn set_and_match_local_pkg_files(
+        &mut self,
+        local_pkg_files: Vec<LocalPackageFiles>,
+    ) -> Result<()> {
         self.local_pkg_files = Some(local_pkg_files.clone());
 
-        match transform::match_local_pkgs_with_gh_pkgs(
+        self.latest_gh_versions = transform::match_local_pkgs_with_gh_pkgs(
             &mut self.latest_gh_versions,
             &local_pkg_files,
-        ) {
-            Ok(matched_versions) => {
-                self.latest_gh_versions = matched_versions;
-            }
-            Err(e) => {
-                panic!(
-                    "Failed to match local packages with GitHub packages: {:?}",
-                    e
-                );
-            }
-        }
+        )?;
+
+        Ok(())
+    }
+
+    /// Exclude a package from selection, e.g. when its local files are inconsistent
+    pub fn remove_package(&mut self, pkg_name: &str) {
+        self.latest_gh_versions.remove(pkg_name);
     }
 
     pub fn set_selected_bump(&mut self, bump: VersionBump) {
@@ -125,6 +318,10 @@ impl AppContext {
         self.target_version = Some(release_info);
     }
 
+    pub fn add_release_target(&mut self, target: ReleaseTarget) {
+        self.release_targets.push(target);
+    }
+
     pub fn find_existing_prerelease(
         &self,
         pkg_name: &str,
@@ -137,15 +334,11 @@ impl AppContext {
             PreReleaseType::Rc => "rc",
         };
 
-        self.all_gh_versions
-            .get(pkg_name)
-            .unwrap()
-            .iter()
-            .find(|rel| {
-                rel.version.major == base_version.major
-                    && rel.version.minor == base_version.minor
-                    && rel.version.patch == base_version.patch
-                    && rel.version.pre.as_str().starts_with(pre_str)
-            })
+        self.all_gh_versions.get(pkg_name)?.iter().find(|rel| {
+            rel.version.major == base_version.major
+                && rel.version.minor == base_version.minor
+                && rel.version.patch == base_version.patch
+                && rel.version.pre.as_str().starts_with(pre_str)
+        })
     }
 }
@@ -0,0 +1,118 @@
+use std::{error::Error, fmt};
+
+/// Everything that can go wrong in rema. Each variant maps to its own exit code, so wrappers
+/// can tell e.g. a user abort apart from a failed rollback.
+#[derive(Debug)]
+pub enum RemaError {
+    /// The user cancelled a prompt or declined to continue
+    Cancelled,
+    /// A prompt could not be shown, e.g. when not running in a terminal
+    Prompt(String),
+    /// Invalid input such as an unknown package, a bad version bump or an invalid config
+    InvalidInput(String),
+    /// A call to the release host failed, or its CLI is missing, unsupported or not logged in
+    Forge(String),
+    /// A git call failed, git is missing or unsupported, or the working tree isn't clean
+    Git(String),
+    /// A tag or version that can't be parsed, or a release that would collide with an existing one
+    BadTag(String),
+    /// Reading or writing package.json, lockfiles or the config failed
+    Manifest(String),
+    /// A release step failed and everything done so far was undone
+    ReleaseFailed(String),
+    /// A release step failed and undoing it failed too, so the repository needs manual cleanup
+    RollbackFailed(String),
+    /// A hook command from the config failed
+    Hook(String),
+}
+
+impl RemaError {
+    /// Process exit code for the error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RemaError::InvalidInput(_) => 2,
+            RemaError::Forge(_) => 3,
+            RemaError::Git(_) => 4,
+            RemaError::BadTag(_) => 5,
+            RemaError::Manifest(_) => 6,
+            RemaError::ReleaseFailed(_) => 7,
+            RemaError::RollbackFailed(_) => 8,
+            RemaError::Prompt(_) => 9,
+            RemaError::Hook(_) => 10,
+            RemaError::Cancelled => 130,
+        }
+    }
+}
+
+impl fmt::Display for RemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemaError::Cancelled => write!(f, "Operation cancelled by user"),
+            RemaError::Prompt(msg) => write!(f, "Prompt failed: {}", msg),
+            RemaError::InvalidInput(msg) => write!(f, "{}", msg),
+            RemaError::Forge(msg) => write!(f, "Forge error: {}", msg),
+            RemaError::Git(msg) => write!(f, "Git error: {}", msg),
+            RemaError::BadTag(msg) => write!(f, "{}", msg),
+            RemaError::Manifest(msg) => write!(f, "{}", msg),
+            RemaError::ReleaseFailed(msg) => write!(f, "Release failed and was rolled back: {}", msg),
+            RemaError::RollbackFailed(msg) => write!(
+                f,
+                "Failed to restore. Check your git history remote and locally to restore manually: {}",
+                msg
+            ),
+            RemaError::Hook(msg) => write!(f, "Hook failed: {}", msg),
+        }
+    }
+}
+
+impl Error for RemaError {}
+
+impl From<inquire::InquireError> for RemaError {
+    fn from(e: inquire::InquireError) -> Self {
+        match e {
+            inquire::InquireError::OperationCanceled
+            | inquire::InquireError::OperationInterrupted => RemaError::Cancelled,
+            e => RemaError::Prompt(e.to_string()),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, RemaError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_map_cancelled_prompts_to_cancelled() {
+        let interrupted: RemaError = inquire::InquireError::OperationInterrupted.into();
+        let canceled: RemaError = inquire::InquireError::OperationCanceled.into();
+        let not_tty: RemaError = inquire::InquireError::NotTTY.into();
+
+        assert!(matches!(interrupted, RemaError::Cancelled));
+        assert!(matches!(canceled, RemaError::Cancelled));
+        assert!(matches!(not_tty, RemaError::Prompt(_)));
+    }
+
+    #[test]
+    fn should_have_distinct_exit_codes() {
+        let errors = [
+            RemaError::Cancelled,
+            RemaError::Prompt(String::new()),
+            RemaError::InvalidInput(String::new()),
+            RemaError::Forge(String::new()),
+            RemaError::Git(String::new()),
+            RemaError::BadTag(String::new()),
+            RemaError::Manifest(String::new()),
+            RemaError::ReleaseFailed(String::new()),
+            RemaError::RollbackFailed(String::new()),
+            RemaError::Hook(String::new()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0) && !codes.contains(&1));
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    cell::OnceCell,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    api::{
+        forge::{self, CreatedRelease, Forge, NewRelease, Release},
+        git::{self, ChangeSummary, CommitInfo},
+        hook, package_manager, ssh,
+    },
+    config::{self, SigningConfig},
+    error::Result,
+    read::PackageManager,
+};
+
+/// The external commands rema runs against the repository and its releases. `SystemIo` shells
+/// out to `git` and the forge; embedders can swap in their own implementation, e.g. to run
+/// against an API client or to test without a repository.
+///
+/// Package files are not part of it: package.json files and lockfiles are read, written and
+/// restored on rollback on the real filesystem, relative to the current directory.
+pub trait Io {
+    /// Fail if the release host can't be reached, e.g. the CLI is missing or too old
+    fn verify_forge(&self) -> Result<()>;
+
+    /// Fail if git is missing or unsupported
+    fn verify_git(&self) -> Result<()>;
+
+    /// Fail if the working tree has uncommitted changes
+    fn verify_clean_worktree(&self) -> Result<()>;
+
+    /// Every release of the repository
+    fn list_releases(&self) -> Result<Vec<Release>>;
+
+    /// Whether the forge can create draft releases
+    fn supports_drafts(&self) -> Result<bool>;
+
+    /// Whether the forge can upload files with a release
+    fn supports_assets(&self) -> Result<bool>;
+
+    /// Create a release and upload its assets
+    fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease>;
+
+    /// Delete a release and its tag. A draft has no tag yet.
+    fn delete_release(&self, tag: &str, is_draft: bool) -> Result<()>;
+
+    /// Publish a draft release, or turn a release back into a draft, and return its URL. A
+    /// `target` commit replaces the one the draft was created at.
+    fn set_release_draft(&self, tag: &str, draft: bool, target: Option<&str>) -> Result<String>;
+
+    /// Commits and changed files in `dir` since `tag`, or in all of its history without a tag
+    fn get_changes_since(&self, tag: Option<&str>, dir: &str) -> Result<ChangeSummary>;
+
+    /// Stage everything and commit it with the given message, signed with the key if one is given
+    fn create_release_commit(&self, message: &str, signing_key: Option<&str>)
+        -> Result<CommitInfo>;
+
+    /// Fail if signing is enabled without a usable key, otherwise return the key to sign with
+    fn verify_signing_key(&self, signing: &SigningConfig) -> Result<String>;
+
+    /// Create a detached signature of a file and return its path
+    fn sign_file(&self, path: &Path, key: &str) -> Result<PathBuf>;
+
+    /// Push the current branch. Returns whether anything was pushed.
+    fn push(&self) -> Result<bool>;
+
+    /// Create an annotated tag on the target commit, HEAD if none, and push it. The tag is signed
+    /// with the key if one is given.
+    fn create_tag(
+        &self,
+        tag: &str,
+        message: &str,
+        target: Option<&str>,
+        signing_key: Option<&str>,
+    ) -> Result<()>;
+
+    /// Delete a tag from `origin` and locally, wherever it still exists
+    fn delete_tag(&self, tag: &str) -> Result<()>;
+
+    fn fetch_tags(&self) -> Result<()>;
+
+    /// Revert a commit with a new commit
+    fn revert_commit(&self, commit: CommitInfo) -> Result<()>;
+
+    /// Tags of the local repository
+    fn list_tags(&self) -> Result<Vec<String>>;
+
+    /// Tags on `origin`
+    fn list_remote_tags(&self) -> Result<Vec<String>>;
+
+    /// Content of a file at a revision, none if it doesn't exist there
+    fn read_file_at(&self, rev: &str, path: &str) -> Result<Option<String>>;
+
+    /// Update the lockfile to the written package.json files with the package manager
+    fn regenerate_lockfile(&self, manager: PackageManager) -> Result<()>;
+
+    /// Run a hook command from the config with the given environment variables
+    fn run_hook(&self, command: &str, env: &[(String, String)]) -> Result<()>;
+
+    /// Write a reproducible tarball of a directory at a revision, with its files under `prefix/`
+    fn create_archive(&self, rev: &str, dir: &str, prefix: &str, output: &Path) -> Result<()>;
+}
+
+/// Runs `git` and the CLI of the forge in the current directory. The forge is detected on first
+/// use unless one is given.
+#[derive(Default)]
+pub struct SystemIo {
+    forge: OnceCell<Box<dyn Forge>>,
+}
+
+impl SystemIo {
+    pub fn with_forge(forge: impl Forge + 'static) -> Self {
+        Self {
+            forge: OnceCell::from(Box::new(forge) as Box<dyn Forge>),
+        }
+    }
+
+    fn forge(&self) -> Result<&dyn Forge> {
+        if let Some(forge) = self.forge.get() {
+            return Ok(forge.as_ref());
+        }
+
+        let forge = forge::detect_forge(&config::load_config()?)?;
+        Ok(self.forge.get_or_init(|| forge).as_ref())
+    }
+}
+
+impl Io for SystemIo {
+    fn verify_forge(&self) -> Result<()> {
+        self.forge()?.verify_auth()
+    }
+
+    fn verify_git(&self) -> Result<()> {
+        git::verify_git_version()
+    }
+
+    fn verify_clean_worktree(&self) -> Result<()> {
+        git::verify_no_outstanding_commits()
+    }
+
+    fn list_releases(&self) -> Result<Vec<Release>> {
+        self.forge()?.list_releases()
+    }
+
+    fn supports_drafts(&self) -> Result<bool> {
+        Ok(self.forge()?.supports_drafts())
+    }
+
+    fn supports_assets(&self) -> Result<bool> {
+        Ok(self.forge()?.supports_assets())
+    }
+
+    fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease> {
+        self.forge()?.create_release(release)
+    }
+
+    fn delete_release(&self, tag: &str, is_draft: bool) -> Result<()> {
+        self.forge()?.delete_release(tag, is_draft)
+    }
+
+    fn set_release_draft(&self, tag: &str, draft: bool, target: Option<&str>) -> Result<String> {
+        self.forge()?.set_release_draft(tag, draft, target)
+    }
+
+    fn get_changes_since(&self, tag: Option<&str>, dir: &str) -> Result<ChangeSummary> {
+        git::get_changes_since(tag, dir)
+    }
+
+    fn create_release_commit(
+        &self,
+        message: &str,
+        signing_key: Option<&str>,
+    ) -> Result<CommitInfo> {
+        git::create_release_commit(&message.to_string(), signing_key)
+    }
+
+    fn verify_signing_key(&self, signing: &SigningConfig) -> Result<String> {
+        let key = git::get_signing_key(signing.key.as_deref())?;
+        if signing.checksums {
+            ssh::verify_signing_key(&key)?;
+        }
+        Ok(key)
+    }
+
+    fn sign_file(&self, path: &Path, key: &str) -> Result<PathBuf> {
+        ssh::sign_file(path, key)
+    }
+
+    fn push(&self) -> Result<bool> {
+        git::push()
+    }
+
+    fn create_tag(
+        &self,
+        tag: &str,
+        message: &str,
+        target: Option<&str>,
+        signing_key: Option<&str>,
+    ) -> Result<()> {
+        git::create_and_push_tag(tag, message, target, signing_key)
+    }
+
+    fn delete_tag(&self, tag: &str) -> Result<()> {
+        git::delete_tag(tag)
+    }
+
+    fn fetch_tags(&self) -> Result<()> {
+        git::fetch_tags()
+    }
+
+    fn revert_commit(&self, commit: CommitInfo) -> Result<()> {
+        git::revert_local_commit(commit)
+    }
+
+    fn list_tags(&self) -> Result<Vec<String>> {
+        Ok(git::list_tags()?.into_iter().map(|(tag, _)| tag).collect())
+    }
+
+    fn list_remote_tags(&self) -> Result<Vec<String>> {
+        git::list_remote_tags()
+    }
+
+    fn read_file_at(&self, rev: &str, path: &str) -> Result<Option<String>> {
+        git::show_file(rev, path)
+    }
+
+    fn regenerate_lockfile(&self, manager: PackageManager) -> Result<()> {
+        package_manager::regenerate_lockfile(manager)
+    }
+
+    fn run_hook(&self, command: &str, env: &[(String, String)]) -> Result<()> {
+        hook::run_hook(command, env)
+    }
+
+    fn create_archive(&self, rev: &str, dir: &str, prefix: &str, output: &Path) -> Result<()> {
+        git::create_archive(rev, dir, prefix, output)
+    }
+}
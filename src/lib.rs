@@ -1,21 +1,64 @@
-use std::error::Error;
+//! Rema is a release manager for npm packages and monorepos, releasing through GitHub, GitLab,
+//! Gitea or Forgejo.
+//!
+//! Besides the `rema` command line tool, the crate can be embedded, e.g. in a release bot. Build
+//! a [`Rema`] with your own [`Io`] and [`Prompt`] to run without a terminal, then load the
+//! context, discover the local packages, plan the releases and execute them:
+//!
+//! ```no_run
+//! use rema::{Rema, VersionBump};
+//!
+//! let rema = Rema::default();
+//! let mut ctx = rema.load_context()?;
+//! rema.discover_packages(&mut ctx)?;
+//! rema.plan_release(&mut ctx, "my-package", VersionBump::Patch, "Bug fixes")?;
+//! let transaction = rema.execute_release(&ctx)?;
+//! println!("{:?}", transaction.report);
+//! # Ok::<(), rema::RemaError>(())
+//! ```
+
+use std::path::{Path, PathBuf};
 
-use api::{
-    gh,
-    git::{self, CommitInfo},
-};
 use colorize::AnsiColor;
-use ctx::AppContext;
-use transform::ReleaseInfo;
+use read::LocalScan;
 use write::WriteTargetResult;
 
+pub use api::{
+    forge::{CreatedRelease, Forge, ForgeKind, NewRelease, Release},
+    gh::GitHub,
+    git::{ChangeSummary, CommitInfo},
+    gitea::Gitea,
+    gitlab::GitLab,
+    tags::GitTags,
+};
+pub use config::{AssetPattern, Config, HooksConfig, LatestConfig, SigningConfig};
+pub use ctx::AppContext;
+pub use error::{RemaError, Result};
+pub use io::{Io, SystemIo};
+pub use prompt::{InquirePrompt, Prompt};
+pub use read::{
+    LocalPackageFiles, LockfileMismatch, LockfileMismatchResolution, PackageJson, PackageLockJson,
+    PackageManager,
+};
+pub use transform::{
+    AssetGlob, AuditFinding, AuditIssue, LatestOverride, NextVersion, PkgStatus, PreReleaseType,
+    PreReleaseVersionBump, PublishedRelease, ReleaseInfo, ReleaseReport, ReleaseResult,
+    ReleaseTarget, VersionBump,
+};
+
 mod api;
 mod cli;
+mod config;
 mod ctx;
+mod error;
+mod io;
+mod prompt;
 mod read;
 mod transform;
 mod write;
 
+use api::{forge, git};
+
 // TODO
 // TODO - write and release phase
 // ? DONE keep backups of the original files to revert if the process below fails
@@ -27,187 +70,833 @@ mod write;
 // TODO - allow selection of which pre to bump if multiple
 
 /// Rema is a tool to help you manage your releases
-pub struct Rema {}
+pub struct Rema {
+    io: Box<dyn Io>,
+    prompt: Box<dyn Prompt>,
+    output: OutputFormat,
+    draft: bool,
+    latest: Vec<LatestOverride>,
+    assets: Vec<AssetGlob>,
+}
+
+/// How commands print their results. With JSON, stdout only carries the JSON document and
+/// human oriented messages go to stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable table
+    #[default]
+    Table,
+    /// Machine readable JSON
+    Json,
+}
+
+/// A release that went through, kept to undo it with [`Rema::rollback`]
+pub struct ReleaseTransaction {
+    pub report: ReleaseReport,
+    local_pkg_backups: WriteTargetResult,
+    commit: Option<CommitInfo>,
+    was_pushed: bool,
+    created_releases: Vec<String>,
+    /// Tags rema pushed itself before creating their release, e.g. signed ones
+    created_tags: Vec<String>,
+    published_drafts: Vec<String>,
+    /// The created releases are drafts, without tags
+    draft: bool,
+}
+
+impl Default for Rema {
+    /// Runs `git` and the forge CLI and asks in the terminal
+    fn default() -> Self {
+        Self::new(SystemIo::default(), InquirePrompt)
+    }
+}
 
 /// Rema is a tool to help you manage your releases
 impl Rema {
-    /// Run the application
-    pub fn run() {
-        Self::requirements_check();
-        let mut ctx = ctx::create_ctx_with_data();
-        if let Some(pkg_files) = read::find_local_pkg_files() {
-            ctx.set_and_match_local_pkg_files(pkg_files);
+    pub fn new(io: impl Io + 'static, prompt: impl Prompt + 'static) -> Self {
+        Self {
+            io: Box::new(io),
+            prompt: Box::new(prompt),
+            output: OutputFormat::default(),
+            draft: false,
+            latest: Vec::new(),
+            assets: Vec::new(),
+        }
+    }
+
+    pub fn with_output(mut self, output: OutputFormat) -> Self {
+        self.output = output;
+        self
+    }
+
+    /// Create releases as drafts, to publish later with [`Rema::publish`]
+    pub fn with_draft(mut self, draft: bool) -> Self {
+        self.draft = draft;
+        self
+    }
+
+    /// Override the `latest` config for this run, per package or for every package
+    pub fn with_latest(mut self, latest: Vec<LatestOverride>) -> Self {
+        self.latest = latest;
+        self
+    }
+
+    /// Upload the files matching these globs with the releases, on top of `assets` in rema.json
+    pub fn with_assets(mut self, assets: Vec<AssetGlob>) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    /// Run the interactive release. A dry run goes through the same prompts but only reports
+    /// what would be written and released.
+    pub fn run(&self, dry_run: bool) -> Result<()> {
+        if dry_run {
+            self.tools_check()?;
+        } else {
+            self.requirements_check()?;
         }
+        let mut ctx = self.load_context()?;
+        self.discover_packages(&mut ctx)?;
+
+        let prerelease_mismatches = transform::find_prerelease_mismatches(ctx.get_releases());
+        if !prerelease_mismatches.is_empty() {
+            cli::print_warning(&format!(
+                "{} releases are marked as pre-release differently than their version says. Rema goes by the version, run `rema audit` for details.",
+                prerelease_mismatches.len()
+            ));
+        }
+
         let pkgs = ctx.get_latest_pkg_names();
+        let changed_pkgs = ctx.get_changed_pkg_names();
+
+        let selected_pkgs = self.prompt.select_packages(pkgs, &changed_pkgs)?;
+
+        for selected_pkg in selected_pkgs {
+            // Already added as a member of the version group of an earlier selection
+            if ctx.is_release_target(&selected_pkg) {
+                continue;
+            }
+            self.configure_release_target(&mut ctx, selected_pkg)?;
+        }
+
+        self.select_dependent_releases(&mut ctx)?;
+
+        if dry_run {
+            return self.print_release_report(&self.plan_report(&ctx));
+        }
+
+        if !self.prompt.input_confirmation(&ctx)? {
+            return Err(RemaError::Cancelled);
+        }
+
+        let transaction = self.execute_release(&ctx)?;
+        self.print_release_report(&transaction.report)
+    }
+
+    fn print_release_report(&self, report: &ReleaseReport) -> Result<()> {
+        match self.output {
+            OutputFormat::Table => {
+                cli::print_release_report(report);
+                Ok(())
+            }
+            OutputFormat::Json => cli::print_json(report),
+        }
+    }
+
+    /// Print an overview of every package: its released and local versions, when it was last
+    /// released and the changes since. Read-only, so lockfile mismatches are only reported.
+    pub fn status(&self) -> Result<()> {
+        self.tools_check()?;
+        let mut ctx = self.load_context()?;
+        self.discover_packages_read_only(&mut ctx)?;
+        ctx.load_pkg_changes(self.io.as_ref());
+
+        let statuses = transform::create_pkg_statuses(&ctx);
+        match self.output {
+            OutputFormat::Table => {
+                cli::print_status(&statuses);
+                Ok(())
+            }
+            OutputFormat::Json => cli::print_json(&statuses),
+        }
+    }
+
+    /// Print the version an interactive release of a package would produce with the given bump,
+    /// e.g. `prerelease:beta`. Side-effect free, so it is safe to call from scripts.
+    pub fn next(&self, pkg_name: &str, bump_spec: &str, print_tag: bool) -> Result<()> {
+        self.tools_check()?;
+        let mut ctx = self.load_context()?;
+        self.discover_packages_read_only(&mut ctx)?;
+
+        let next = transform::compute_next_version(&ctx, pkg_name, bump_spec)?;
+
+        match self.output {
+            OutputFormat::Table if print_tag => println!("{}", next.tag),
+            OutputFormat::Table => println!("{}", next.version),
+            OutputFormat::Json => cli::print_json(&next)?,
+        }
+        Ok(())
+    }
+
+    /// Publish a draft release, e.g. once its assets are uploaded and it passed QA. This creates
+    /// its tag.
+    pub fn publish(&self, tag: &str) -> Result<()> {
+        self.tools_check()?;
+
+        let releases = self.io.list_releases()?;
+        match releases.iter().find(|release| release.tag_name == tag) {
+            None => {
+                return Err(RemaError::InvalidInput(format!(
+                    "No release for tag {}",
+                    tag
+                )))
+            }
+            Some(release) if !release.is_draft => {
+                return Err(RemaError::InvalidInput(format!(
+                    "The release of {} is already published",
+                    tag
+                )))
+            }
+            Some(_) => {}
+        }
+
+        let url = self.io.set_release_draft(tag, false, None)?;
+        let published = PublishedRelease {
+            tag: tag.to_string(),
+            release_url: Some(url).filter(|url| !url.is_empty()),
+        };
+
+        match self.output {
+            OutputFormat::Table => {
+                cli::print_published_release(&published);
+                Ok(())
+            }
+            OutputFormat::Json => cli::print_json(&published),
+        }
+    }
+
+    /// List inconsistencies between the release tags, the releases and the package.json at each
+    /// tag, e.g. left behind by manual releases. Read-only.
+    pub fn audit(&self) -> Result<()> {
+        self.tools_check()?;
+        let mut ctx = self.load_context()?;
+        self.discover_packages_read_only(&mut ctx)?;
+
+        let findings = self.audit_findings(&ctx)?;
+        match self.output {
+            OutputFormat::Table => {
+                cli::print_audit(&findings);
+                Ok(())
+            }
+            OutputFormat::Json => cli::print_json(&findings),
+        }
+    }
+
+    /// Inconsistencies between the release tags, the releases and the package manifests
+    pub fn audit_findings(&self, ctx: &AppContext) -> Result<Vec<AuditFinding>> {
+        let local_tags = self.io.list_tags()?;
+        let remote_tags = self.io.list_remote_tags()?;
+        let mut findings =
+            transform::create_audit_findings(ctx.get_releases(), &local_tags, &remote_tags);
 
-        // let local_pkgs = ctx.get_local_pkg_files().unwrap_or_else(|| {
-        //     panic!("Failed to get local package files");
-        // });
-        // println!("Local packages: {:#?}", local_pkgs);
+        for tag in &local_tags {
+            let Some((pkg_name, _)) = transform::parse_release_tag(tag) else {
+                continue;
+            };
+            // Packages that no longer exist locally can't be located at the tag
+            let Some(dir) = ctx
+                .get_latest_versions()
+                .get(pkg_name)
+                .and_then(|info| info.local_pkg_files.as_ref())
+                .and_then(|files| files.dir.clone())
+            else {
+                continue;
+            };
+            let path = if dir == "." {
+                "package.json".to_string()
+            } else {
+                format!("{}/package.json", dir)
+            };
 
-        let selected_pkg = cli::select_pkg_name(pkgs)
-            .unwrap_or_else(|e| panic!("Failed to select package {:?}", Some(e)));
+            if let Some(manifest) = self.io.read_file_at(tag, &path)? {
+                findings.extend(transform::check_manifest_version(tag, &manifest));
+            }
+        }
+
+        findings.sort_by(|a, b| a.tag.cmp(&b.tag).then(a.issue.cmp(&b.issue)));
+        Ok(findings)
+    }
+
+    /// Load the releases of the repository and the `rema.json` config
+    pub fn load_context(&self) -> Result<AppContext> {
+        let mut ctx = ctx::create_ctx_with_data(self.io.as_ref())?;
+        ctx.set_output_format(self.output);
+        ctx.set_draft(self.draft);
+        ctx.set_latest_overrides(self.latest.clone());
+        ctx.set_asset_globs(self.assets.clone());
+        Ok(ctx)
+    }
+
+    /// Find the local packages and match them with their releases, then load the changes since
+    /// their latest release. Lockfile mismatches are resolved through the prompt. Resynced
+    /// lockfiles are only written with the release.
+    pub fn discover_packages(&self, ctx: &mut AppContext) -> Result<()> {
+        if let Some(scan) = read::find_local_pkg_files()? {
+            ctx.set_private_pkg_files(scan.private_pkg_files.clone());
+            let (pkg_files, skipped_pkgs) = self.resolve_lockfile_mismatches(ctx, scan)?;
+            ctx.set_and_match_local_pkg_files(pkg_files)?;
+            for pkg_name in skipped_pkgs {
+                ctx.remove_package(&pkg_name);
+            }
+        }
+        ctx.load_pkg_changes(self.io.as_ref());
+
+        Ok(())
+    }
+
+    fn discover_packages_read_only(&self, ctx: &mut AppContext) -> Result<()> {
+        if let Some(scan) = read::find_local_pkg_files()? {
+            if !scan.lockfile_mismatches.is_empty() {
+                cli::print_lockfile_diagnostics(&scan.lockfile_mismatches);
+            }
+            ctx.set_private_pkg_files(scan.private_pkg_files);
+            ctx.set_and_match_local_pkg_files(scan.pkg_files)?;
+        }
+
+        Ok(())
+    }
+
+    /// The release a bump of a package would produce, without planning it
+    pub fn compute_bump(
+        ctx: &AppContext,
+        pkg_name: &str,
+        bump: VersionBump,
+    ) -> Result<ReleaseInfo> {
+        transform::bump_pkg_version(ctx, pkg_name, bump)
+    }
+
+    /// Plan the release of a package, and of the other members of its version group, without
+    /// asking anything. The title is the generated tag.
+    pub fn plan_release(
+        &self,
+        ctx: &mut AppContext,
+        pkg_name: &str,
+        bump: VersionBump,
+        description: &str,
+    ) -> Result<()> {
+        let release_info = transform::bump_pkg_version(ctx, pkg_name, bump)?;
+        let previous_version = ctx.get_latest_versions()[pkg_name].version.clone();
+        let title = transform::create_pkg_release_title(pkg_name, &release_info);
+        let publish_draft = ctx.find_draft_release(&title).is_some();
+
+        self.add_release_target_with_group(
+            ctx,
+            ReleaseTarget {
+                pkg_name: pkg_name.to_string(),
+                previous_version,
+                release_info,
+                title,
+                description: description.to_string(),
+                publish_draft,
+            },
+            false,
+        )
+    }
+
+    /// What executing the planned releases would do, without doing it
+    pub fn plan_report(&self, ctx: &AppContext) -> ReleaseReport {
+        let changed_files = write::list_files_to_update(ctx);
+        transform::create_release_report(ctx, &changed_files, &[], true)
+    }
 
-        let selected_pkg = selected_pkg.replace("(unreleased)", "").trim().to_string();
+    /// Prompt for the bump, title and description of one selected package and add it to the
+    /// releases of this run
+    fn configure_release_target(&self, ctx: &mut AppContext, selected_pkg: String) -> Result<()> {
         ctx.set_selected_package(selected_pkg.clone());
 
         let latest_versions = ctx.get_latest_versions().clone();
 
-        let selected_pkg_release_info = latest_versions.get(&selected_pkg).unwrap_or_else(|| {
-            panic!("Failed to get version for package: {}", selected_pkg);
-        });
+        let selected_pkg_release_info = latest_versions
+            .get(&selected_pkg)
+            .ok_or_else(|| RemaError::InvalidInput(format!("Unknown package: {}", selected_pkg)))?;
 
-        let is_local_only = selected_pkg_release_info.local_only.clone();
+        let is_local_only = selected_pkg_release_info.local_only;
         let release_status_mgs = if is_local_only {
             "locally set"
         } else {
             "released"
         };
 
-        println!(
-            "  {} is currently {} as version {}",
-            selected_pkg.clone().green().underlined(),
-            release_status_mgs,
-            selected_pkg_release_info.version.clone().to_string().cyan()
+        let pkg_dir_msg = selected_pkg_release_info
+            .local_pkg_files
+            .as_ref()
+            .and_then(|files| files.dir.clone())
+            .map(|dir| format!(" ({})", dir))
+            .unwrap_or_default();
+
+        cli::print_message(
+            ctx,
+            &format!(
+                "  {}{} is currently {} as version {}",
+                selected_pkg.clone().green().underlined(),
+                pkg_dir_msg.grey(),
+                release_status_mgs,
+                selected_pkg_release_info.version.clone().to_string().cyan()
+            ),
         );
 
-        let selected_bump = cli::select_version_bump(&ctx)
-            .unwrap_or_else(|e| panic!("Failed to select version bump {:?}", Some(e)));
+        let group_members = ctx.get_version_group_members(&selected_pkg);
+        if !group_members.is_empty() {
+            cli::print_message(
+                ctx,
+                &format!(
+                    "  Released together with {} (version group)",
+                    group_members.join(", ").cyan()
+                ),
+            );
+        }
+
+        let selected_bump = self.prompt.select_version_bump(ctx)?;
 
         ctx.set_selected_bump(selected_bump.clone());
 
-        let target_release_info = transform::bump_version(&ctx, selected_bump);
+        let target_release_info = transform::bump_version(ctx, selected_bump)?;
         ctx.set_target_release_info(target_release_info.clone());
 
-        let initial_release_title = transform::create_release_title(&ctx);
+        let initial_release_title = transform::create_release_title(ctx)?;
+
+        let target_title = self
+            .prompt
+            .input_release_title(initial_release_title.as_str())?;
+
+        // Publishing a draft keeps its notes, so there is no description to ask for
+        let publish_draft = ctx.find_draft_release(&target_title).is_some()
+            && self.prompt.input_publish_draft(&target_title)?;
+        let target_description = if publish_draft {
+            String::new()
+        } else {
+            self.prompt.input_release_description(ctx)?
+        };
+
+        self.add_release_target_with_group(
+            ctx,
+            ReleaseTarget {
+                pkg_name: selected_pkg,
+                previous_version: selected_pkg_release_info.version.clone(),
+                release_info: target_release_info,
+                title: target_title,
+                description: target_description,
+                publish_draft,
+            },
+            true,
+        )
+    }
+
+    /// Add a release target along with the other members of its version group, which are
+    /// released at the same version with the same description. Members with a draft release of
+    /// their tag publish it, after asking through the prompt when `ask_drafts` is set.
+    fn add_release_target_with_group(
+        &self,
+        ctx: &mut AppContext,
+        target: ReleaseTarget,
+        ask_drafts: bool,
+    ) -> Result<()> {
+        let group_releases = transform::create_version_group_release_infos(
+            ctx,
+            &target.pkg_name,
+            &target.release_info,
+        );
 
-        let target_title =
-            cli::input_release_title(initial_release_title.as_str()).unwrap_or_else(|e| {
-                panic!("Failed to input release title {:?}", Some(e));
+        for (pkg_name, release_info) in group_releases {
+            if ctx.is_release_target(&pkg_name) {
+                continue;
+            }
+            let previous_version = ctx.get_latest_versions()[&pkg_name].version.clone();
+            let title = transform::create_pkg_release_title(&pkg_name, &release_info);
+            let publish_draft = ctx.find_draft_release(&title).is_some()
+                && (!ask_drafts || self.prompt.input_publish_draft(&title)?);
+            ctx.add_release_target(ReleaseTarget {
+                pkg_name,
+                previous_version,
+                release_info,
+                title,
+                description: target.description.clone(),
+                publish_draft,
             });
+        }
 
-        let target_description = cli::input_release_description(&ctx).unwrap_or_else(|e| {
-            panic!("Failed to input release description {:?}", Some(e));
-        });
+        ctx.add_release_target(target);
 
-        let is_confirmed = cli::input_confirmation(&ctx).unwrap_or_else(|e| {
-            panic!("Error occurred during confirmation {:?}", Some(e));
-        });
+        Ok(())
+    }
+
+    /// Offer to patch release the local packages that depend on the selected packages. Their
+    /// dependency ranges are updated regardless, except to pre-releases they don't already use.
+    fn select_dependent_releases(&self, ctx: &mut AppContext) -> Result<()> {
+        let graph = ctx.get_dependency_graph();
+        let mut dependents: Vec<String> = Vec::new();
 
-        if !is_confirmed {
-            println!("Aborted");
-            return;
+        for target in ctx.get_release_targets() {
+            for dependent in graph.get_transitive_dependents(&target.pkg_name) {
+                if !dependents.contains(&dependent)
+                    && !ctx.is_release_target(&dependent)
+                    && ctx.get_latest_versions().contains_key(&dependent)
+                {
+                    dependents.push(dependent);
+                }
+            }
         }
+        dependents.sort();
 
-        match Self::execute_release_transaction(
-            &ctx,
-            &target_release_info,
-            target_description,
-            target_title,
-        ) {
-            Ok(()) => println!(
-                "Release completed successfully! Bumped version for {} from {} to {}",
-                selected_pkg, selected_pkg_release_info.version, target_release_info.version
-            ),
-            Err(e) => eprintln!("Release failed and was rolled back: {}", e),
+        if dependents.is_empty() || !self.prompt.input_release_dependents(&dependents)? {
+            return Ok(());
         }
 
-        // println!("has v prefix: {}", target_release_info.has_v_prefix);
-        // println!("title: {}", target_title);
-        // println!("target_description: {}", target_description);
-        // println!("local_pkgs: {:#?}", target_release_info.local_pkg_files);
+        for pkg_name in dependents {
+            // A group member of an earlier dependent is already released along with it
+            if ctx.is_release_target(&pkg_name) {
+                continue;
+            }
+            let previous_version = ctx.get_latest_versions()[&pkg_name].version.clone();
+            let release_info = transform::bump_pkg_version(ctx, &pkg_name, VersionBump::Patch)?;
+            let title = transform::create_pkg_release_title(&pkg_name, &release_info);
+            let publish_draft = ctx.find_draft_release(&title).is_some()
+                && self.prompt.input_publish_draft(&title)?;
+            self.add_release_target_with_group(
+                ctx,
+                ReleaseTarget {
+                    pkg_name,
+                    previous_version,
+                    release_info,
+                    title,
+                    description: String::new(),
+                    publish_draft,
+                },
+                true,
+            )?;
+        }
+
+        Ok(())
     }
 
-    fn requirements_check() {
-        let gh_cli = gh::verify_gh_cli_version().unwrap_or_else(|e| {
-            panic!("GitHub CLI check failed: {:?}", Some(e).unwrap());
-        });
+    /// Report lockfiles that are out of sync with their package.json and let the user either
+    /// resync them or skip the package. Returns the usable package files and the skipped names.
+    /// Resyncs are added to the context, to be written with the release.
+    fn resolve_lockfile_mismatches(
+        &self,
+        ctx: &mut AppContext,
+        scan: LocalScan,
+    ) -> Result<(Vec<LocalPackageFiles>, Vec<String>)> {
+        let mut pkg_files = scan.pkg_files;
+        let mut skipped_pkgs = Vec::new();
 
-        if !gh_cli {
-            panic!("GitHub CLI is not installed");
+        if scan.lockfile_mismatches.is_empty() {
+            return Ok((pkg_files, skipped_pkgs));
         }
 
-        let git = git::verify_git_version().unwrap_or_else(|e| {
-            panic!("Git check failed: {:?}", Some(e).unwrap());
-        });
+        cli::print_lockfile_diagnostics(&scan.lockfile_mismatches);
+
+        for mismatch in scan.lockfile_mismatches {
+            if mismatch
+                .pkg_name
+                .as_ref()
+                .is_some_and(|name| skipped_pkgs.contains(name))
+            {
+                continue;
+            }
+
+            let resolution = self.prompt.select_lockfile_mismatch_resolution(&mismatch)?;
+
+            match resolution {
+                LockfileMismatchResolution::Resync => {
+                    ctx.add_lockfile_resync(mismatch.clone());
+
+                    let mut lock = mismatch.package_lock_json;
+                    lock.version = mismatch.package_json.version.clone();
+                    if let Some(pkg) = pkg_files
+                        .iter_mut()
+                        .find(|pkg| pkg.name == mismatch.pkg_name)
+                    {
+                        if lock.workspace_key.is_some() {
+                            pkg.workspace_package_lock_json = Some(lock);
+                        } else {
+                            pkg.package_lock_json = Some(lock);
+                        }
+                    }
+                }
+                LockfileMismatchResolution::Skip => {
+                    pkg_files.retain(|pkg| pkg.name != mismatch.pkg_name);
+                    if let Some(pkg_name) = mismatch.pkg_name {
+                        skipped_pkgs.push(pkg_name);
+                    }
+                }
+            }
+        }
+
+        Ok((pkg_files, skipped_pkgs))
+    }
+
+    /// Everything a release needs, checked before any of its steps run
+    fn requirements_check(&self) -> Result<()> {
+        self.tools_check()?;
+        self.io.verify_clean_worktree()?;
 
-        if !git {
-            panic!("Git is not installed");
+        let signing = config::load_config()?.signing;
+        if signing.is_enabled() {
+            self.io.verify_signing_key(&signing)?;
         }
 
-        git::verify_no_outstanding_commits().unwrap_or_else(|e| {
-            panic!("{:?}", Some(e).unwrap());
-        });
+        Ok(())
     }
 
-    /// Release procedure. If any step fails, will attempt to restore to original state and
-    /// in the case of commits and pushes reverts will be employed.
-    ///
-    /// Manual test with `return Err("Test error message".into());`
-    fn execute_release_transaction(
-        ctx: &AppContext,
-        target_release_info: &ReleaseInfo,
-        target_description: String,
-        target_title: String,
-    ) -> Result<(), Box<dyn Error>> {
-        // Step 1: Create backups first
-        let local_pkg_backups = match write::write_target_release_to_local_files(ctx) {
-            Ok(backups) => backups,
-            Err(e) => return Err(format!("Failed to write to local package files: {:?}", e).into()),
+    fn tools_check(&self) -> Result<()> {
+        self.io.verify_forge()?;
+        self.io.verify_git()
+    }
+
+    /// Release procedure for every release target: one commit, one push and a release per
+    /// package, with the hooks of the config in between. If any step fails, will attempt to
+    /// restore to original state as a unit, deleting the releases already created and, in the
+    /// case of commits and pushes, employing reverts.
+    pub fn execute_release(&self, ctx: &AppContext) -> Result<ReleaseTransaction> {
+        let targets = ctx.get_release_targets();
+        let changed_files = write::list_files_to_update(ctx);
+        let signing = &ctx.get_config().signing;
+        let signing_key = if signing.is_enabled() {
+            Some(self.io.verify_signing_key(signing)?)
+        } else {
+            None
         };
+
+        self.check_release_support(ctx)?;
+        let hooks = &ctx.get_config().hooks;
+        // Hooks that run after the writes may build the assets, which are then found after them
+        let builds_assets = !hooks.post_write.is_empty() || !hooks.pre_commit.is_empty();
+        let mut target_assets = if builds_assets {
+            None
+        } else {
+            Some(find_target_assets(ctx)?)
+        };
+        self.run_hooks(ctx, "pre_bump", &hooks.pre_bump)?;
+
+        let root_lockfile = if ctx.get_config().regenerate_lockfiles {
+            read::find_root_lockfile()?
+        } else {
+            None
+        };
+
+        // Step 1: Create backups first. Failed writes restore the files already written.
+        let local_pkg_backups = write::write_target_release_to_local_files(
+            ctx,
+            root_lockfile.as_ref().map(|(_, path)| path.as_path()),
+        )?;
         let mut commit_info: Option<CommitInfo> = None;
         let mut was_pushed = false;
+        let mut created_releases: Vec<String> = Vec::new();
+        let mut created_tags: Vec<String> = Vec::new();
+        let mut published_drafts: Vec<String> = Vec::new();
+        let mut created: Vec<CreatedRelease> = Vec::new();
 
         // Step 2: Execute each operation in sequence, rolling back on failure
-        let result: Result<(), Box<dyn Error>> = (|| {
-            commit_info = Some(
-                git::create_release_commit(&target_title)
-                    .map_err(|e| format!("Failed to make git commit: {:?}", e))?,
-            );
+        let result: Result<()> = (|| {
+            if let Some((manager, _)) = &root_lockfile {
+                self.io.regenerate_lockfile(*manager)?;
+            }
+            self.run_tree_hooks(ctx, "post_write", &hooks.post_write)?;
+            self.run_tree_hooks(ctx, "pre_commit", &hooks.pre_commit)?;
+            let target_assets = match target_assets.take() {
+                Some(target_assets) => target_assets,
+                None => find_target_assets(ctx)?,
+            };
 
-            was_pushed = git::push().map_err(|e| format!("Failed to push changes: {:?}", e))?;
+            let commit_message = targets
+                .iter()
+                .map(|target| target.title.clone())
+                .collect::<Vec<String>>()
+                .join(", ");
 
-            gh::create_release(&target_release_info, target_description, target_title)
-                .map_err(|e| format!("Failed to create release: {:?}", e))?;
+            commit_info = Some(self.io.create_release_commit(
+                &commit_message,
+                signing_key.as_deref().filter(|_| signing.commits),
+            )?);
 
-            git::fetch_tags().map_err(|e| format!("Failed to fetch tags: {:?}", e))?;
+            was_pushed = self.io.push()?;
 
-            Ok(())
+            for (target, assets) in targets.iter().zip(target_assets) {
+                // A draft got its assets when it was created. Its tag goes on the release commit,
+                // which has the new versions, not on the commit the draft was created at.
+                let release = if target.publish_draft {
+                    let url = self.io.set_release_draft(
+                        &target.title,
+                        false,
+                        commit_info.as_ref().map(|commit| commit.sha.as_str()),
+                    )?;
+                    published_drafts.push(target.title.clone());
+                    CreatedRelease {
+                        url,
+                        asset_urls: Vec::new(),
+                    }
+                } else {
+                    let (assets, description) = prepare_release_assets(
+                        self.io.as_ref(),
+                        ctx,
+                        target,
+                        assets,
+                        commit_info.as_ref(),
+                        signing_key.as_deref().filter(|_| signing.checksums),
+                    )?;
+                    let tag_signing_key = signing_key.as_deref().filter(|_| signing.tags);
+                    let release = NewRelease {
+                        title: target.title.clone(),
+                        description,
+                        prerelease: !target.release_info.version.pre.is_empty(),
+                        draft: ctx.is_draft(),
+                        // A draft gets its tag when published, which must not move past the
+                        // release commit in the meantime
+                        target: commit_info.as_ref().map(|commit| commit.sha.clone()),
+                        latest: transform::resolve_latest(ctx, target),
+                        assets,
+                        tag_exists: tag_signing_key.is_some(),
+                    };
+                    // Forges would tag the release themselves, unsigned
+                    if let Some(key) = tag_signing_key {
+                        self.io.create_tag(
+                            &release.title,
+                            release.tag_message(),
+                            release.target.as_deref(),
+                            Some(key),
+                        )?;
+                        created_tags.push(target.title.clone());
+                    }
+                    let release = self.io.create_release(&release)?;
+                    created_releases.push(target.title.clone());
+                    release
+                };
+                created.push(release);
+            }
+
+            self.io.fetch_tags()?;
+
+            self.run_hooks(ctx, "post_release", &hooks.post_release)
         })();
 
+        let transaction = ReleaseTransaction {
+            report: transform::create_release_report(ctx, &changed_files, &created, false),
+            local_pkg_backups,
+            commit: commit_info,
+            was_pushed,
+            created_releases,
+            created_tags,
+            published_drafts,
+            draft: ctx.is_draft(),
+        };
+
         // If any step failed, restore from backups
-        if result.is_err() {
-            Self::restore_backups(&local_pkg_backups, commit_info, was_pushed)
-                .map_err(|e| format!("Failed to restore. Check your git history remote and locally to restore manually: {:?}", e))?;
+        if let Err(e) = result {
+            self.rollback(transaction).map_err(|restore_error| {
+                RemaError::RollbackFailed(format!("{} (release error: {})", restore_error, e))
+            })?;
+
+            return Err(RemaError::ReleaseFailed(e.to_string()));
+        }
+
+        Ok(transaction)
+    }
+
+    /// Reject releases the forge or the signing config can't make before anything is written, so
+    /// they don't fail after the release commit is pushed
+    fn check_release_support(&self, ctx: &AppContext) -> Result<()> {
+        let targets = ctx.get_release_targets();
+        let publishes_drafts = targets.iter().any(|target| target.publish_draft);
+
+        if ctx.is_draft() && !self.io.supports_drafts()? {
+            return Err(RemaError::InvalidInput(
+                "The forge has no draft releases".to_string(),
+            ));
+        }
+        if (ctx.is_draft() || publishes_drafts) && ctx.get_config().signing.tags {
+            return Err(RemaError::InvalidInput(
+                "Drafts get their tag when they're published, so it can't be signed".to_string(),
+            ));
+        }
+
+        let has_assets = ctx.get_config().source_archive
+            || targets.iter().any(|target| {
+                !target.publish_draft && !transform::resolve_asset_patterns(ctx, target).is_empty()
+            });
+        if has_assets && !self.io.supports_assets()? {
+            return Err(RemaError::InvalidInput(
+                "The forge can't upload release assets".to_string(),
+            ));
         }
 
-        result
+        Ok(())
     }
 
-    // Create a cleanup function that will run on error
-    fn restore_backups(
-        result: &WriteTargetResult,
-        commit_info: Option<CommitInfo>,
-        was_pushed: bool,
-    ) -> Result<(), Box<dyn Error>> {
-        if let Some(commit) = commit_info {
-            git::revert_local_commit(commit)
-                .map_err(|e| format!("Failed to revert local commit: {:?}", e))?;
-            if was_pushed {
-                git::push()
-                    .map_err(|e| format!("Failed to push after reverting local commit: {:?}", e))?;
+    /// Run the commands of a hook for every release target
+    fn run_hooks(&self, ctx: &AppContext, hook: &str, commands: &[String]) -> Result<()> {
+        for target in ctx.get_release_targets() {
+            let env = transform::create_hook_env(hook, target);
+            for command in commands {
+                self.io.run_hook(command, &env)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the commands of a hook on the working tree once for all release targets
+    fn run_tree_hooks(&self, ctx: &AppContext, hook: &str, commands: &[String]) -> Result<()> {
+        let env = transform::create_tree_hook_env(hook, ctx.get_release_targets());
+        for command in commands {
+            self.io.run_hook(command, &env)?;
+        }
+        Ok(())
+    }
+
+    /// Undo a release, e.g. when a step after it failed
+    pub fn rollback(&self, transaction: ReleaseTransaction) -> Result<()> {
+        self.restore_backups(transaction)
+            .map_err(|e| RemaError::RollbackFailed(e.to_string()))
+    }
+
+    /// Undo the completed steps of a release: delete the created releases, turn published drafts
+    /// back into drafts and delete the tags of both, then revert the release commit, or restore the written
+    /// files if nothing was committed yet
+    fn restore_backups(&self, transaction: ReleaseTransaction) -> Result<()> {
+        for tag in &transaction.created_releases {
+            self.io.delete_release(tag, transaction.draft)?;
+        }
+        for tag in &transaction.published_drafts {
+            self.io.set_release_draft(tag, true, None)?;
+        }
+        // Forges only delete their tags remotely, while pushed and fetched tags are also local.
+        // Published drafts got their tag when published, and lose it once they are drafts again.
+        let mut tags: Vec<&String> = transaction.created_tags.iter().collect();
+        if !transaction.draft {
+            tags.extend(&transaction.created_releases);
+        }
+        tags.extend(&transaction.published_drafts);
+        tags.sort();
+        tags.dedup();
+        for tag in tags {
+            self.io.delete_tag(tag)?;
+        }
+
+        if let Some(commit) = transaction.commit {
+            self.io.revert_commit(commit)?;
+            if transaction.was_pushed {
+                self.io.push()?;
             }
 
             return Ok(());
         }
 
-        if let WriteTargetResult::WritesCompleted {
-            original_pkg_json,
-            original_pkg_json_lock,
-        } = result
+        if let WriteTargetResult::WritesCompleted { original_files } =
+            &transaction.local_pkg_backups
         {
-            // Restore package.json if it exists
-            if let Some(original) = original_pkg_json {
+            // Restore every package.json and lockfile that was written. Like the writes, this goes
+            // straight to the filesystem rather than through `Io`.
+            for original in original_files {
                 if let Err(e) = std::fs::write(&original.path, &original.contents) {
                     eprintln!(
                         "Warning: Failed to restore backup for {}: {:?}",
@@ -216,18 +905,791 @@ impl Rema {
                     );
                 }
             }
+        }
+        Ok(()) // No action needed for NoWrites variant
+    }
+}
 
-            // Restore package-lock.json if it exists
-            if let Some(original) = original_pkg_json_lock {
-                if let Err(e) = std::fs::write(&original.path, &original.contents) {
-                    eprintln!(
-                        "Warning: Failed to restore backup for {}: {:?}",
-                        original.path.as_str(),
-                        e
-                    );
-                }
+/// Files matching the asset globs of every release target, in order. Fails when a required glob
+/// matches nothing. Drafts to publish got their assets when they were created.
+fn find_target_assets(ctx: &AppContext) -> Result<Vec<Vec<PathBuf>>> {
+    ctx.get_release_targets()
+        .iter()
+        .map(|target| {
+            if target.publish_draft {
+                return Ok(Vec::new());
             }
+            read::find_release_assets(
+                Path::new(&transform::get_pkg_dir(target)),
+                &transform::resolve_asset_patterns(ctx, target),
+            )
+        })
+        .collect()
+}
+
+/// Add the source tarball at the release commit to the found assets of a release when configured
+/// and a `SHA256SUMS` file for them. Returns the files to upload and the release description,
+/// with a checksum table when configured.
+fn prepare_release_assets(
+    io: &dyn Io,
+    ctx: &AppContext,
+    target: &ReleaseTarget,
+    mut assets: Vec<PathBuf>,
+    commit: Option<&CommitInfo>,
+    checksums_signing_key: Option<&str>,
+) -> Result<(Vec<PathBuf>, String)> {
+    let pkg_dir = transform::get_pkg_dir(target);
+    if let (true, Some(commit)) = (ctx.get_config().source_archive, commit) {
+        let name = transform::create_source_archive_name(&target.title);
+        let archive =
+            write::create_release_files_dir(&target.title)?.join(format!("{}.tar.gz", name));
+        io.create_archive(&commit.sha, &pkg_dir, &name, &archive)?;
+        assets.push(archive);
+    }
+    if assets.is_empty() {
+        return Ok((assets, target.description.clone()));
+    }
+
+    let checksums = assets
+        .iter()
+        .map(|asset| {
+            let name = asset
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            Ok((name, read::sha256_file(asset)?))
+        })
+        .collect::<Result<Vec<(String, String)>>>()?;
+    let checksums_file = write::write_release_file(
+        &target.title,
+        transform::CHECKSUMS_FILE_NAME,
+        &transform::create_checksums_file(&checksums),
+    )?;
+    let signature = checksums_signing_key
+        .map(|key| io.sign_file(&checksums_file, key))
+        .transpose()?;
+    assets.push(checksums_file);
+    assets.extend(signature);
+
+    let description = if ctx.get_config().checksum_table {
+        let table = transform::create_checksum_table(&checksums);
+        if target.description.is_empty() {
+            table
+        } else {
+            format!("{}\n\n{}", target.description, table)
         }
-        Ok(()) // No action needed for NoWrites variant
+    } else {
+        target.description.clone()
+    };
+
+    Ok((assets, description))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    /// Records every call and fails to create the release of `failing_tag`. A `tags_only` forge
+    /// has neither drafts nor assets.
+    #[derive(Default)]
+    struct MockIo {
+        calls: RefCell<Vec<String>>,
+        failing_tag: String,
+        releases: Vec<Release>,
+        tags_only: bool,
+    }
+
+    impl MockIo {
+        fn record(&self, call: String) {
+            self.calls.borrow_mut().push(call);
+        }
+    }
+
+    impl Io for Rc<MockIo> {
+        fn verify_forge(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn verify_git(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn verify_clean_worktree(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn list_releases(&self) -> Result<Vec<Release>> {
+            Ok(self.releases.clone())
+        }
+
+        fn supports_drafts(&self) -> Result<bool> {
+            Ok(!self.tags_only)
+        }
+
+        fn supports_assets(&self) -> Result<bool> {
+            Ok(!self.tags_only)
+        }
+
+        fn create_release(&self, release: &NewRelease) -> Result<CreatedRelease> {
+            let title = &release.title;
+            if *title == self.failing_tag {
+                return Err(RemaError::Forge("Release already exists".to_string()));
+            }
+            if release.draft {
+                let target = release.target.as_deref().unwrap_or_default();
+                self.record(format!("create draft {} at {}", title, target));
+            } else if release.tag_exists {
+                self.record(format!("create {} on existing tag", title));
+            } else {
+                self.record(format!("create {}", title));
+            }
+            Ok(CreatedRelease {
+                url: format!("https://example.com/releases/{}", title),
+                asset_urls: release
+                    .assets
+                    .iter()
+                    .map(|asset| format!("https://example.com/assets/{}", asset.display()))
+                    .collect(),
+            })
+        }
+
+        fn delete_release(&self, tag: &str, is_draft: bool) -> Result<()> {
+            if is_draft {
+                self.record(format!("delete draft {}", tag));
+            } else {
+                self.record(format!("delete {}", tag));
+            }
+            Ok(())
+        }
+
+        fn set_release_draft(
+            &self,
+            tag: &str,
+            draft: bool,
+            target: Option<&str>,
+        ) -> Result<String> {
+            match target {
+                Some(target) => self.record(format!("draft={} {} at {}", draft, tag, target)),
+                None => self.record(format!("draft={} {}", draft, tag)),
+            }
+            Ok(format!("https://example.com/releases/{}", tag))
+        }
+
+        fn get_changes_since(&self, _tag: Option<&str>, _dir: &str) -> Result<ChangeSummary> {
+            Ok(ChangeSummary::default())
+        }
+
+        fn create_release_commit(
+            &self,
+            message: &str,
+            signing_key: Option<&str>,
+        ) -> Result<CommitInfo> {
+            match signing_key {
+                Some(key) => self.record(format!("commit {} signed by {}", message, key)),
+                None => self.record(format!("commit {}", message)),
+            }
+            Ok(CommitInfo {
+                sha: "abc123".to_string(),
+                _message: message.to_string(),
+            })
+        }
+
+        fn verify_signing_key(&self, signing: &SigningConfig) -> Result<String> {
+            Ok(signing
+                .key
+                .clone()
+                .unwrap_or("~/.ssh/id_ed25519".to_string()))
+        }
+
+        fn sign_file(&self, path: &Path, key: &str) -> Result<PathBuf> {
+            let file_name = path.file_name().unwrap().to_string_lossy();
+            self.record(format!("sign {} with {}", file_name, key));
+            Ok(path.with_file_name(format!("{}.sig", file_name)))
+        }
+
+        fn regenerate_lockfile(&self, manager: PackageManager) -> Result<()> {
+            self.record(format!("regenerate {}", manager.lockfile_name()));
+            Ok(())
+        }
+
+        fn run_hook(&self, command: &str, env: &[(String, String)]) -> Result<()> {
+            let tag = &env
+                .iter()
+                .find(|(key, _)| key == "REMA_TAG" || key == "REMA_TAGS")
+                .unwrap()
+                .1;
+            self.record(format!("hook {} for {}", command, tag));
+            if command == "false" {
+                return Err(RemaError::Hook("false exited with 1".to_string()));
+            }
+            Ok(())
+        }
+
+        fn create_archive(&self, rev: &str, dir: &str, prefix: &str, output: &Path) -> Result<()> {
+            self.record(format!("archive {} at {} as {}", dir, rev, prefix));
+            std::fs::write(output, prefix).unwrap();
+            Ok(())
+        }
+
+        fn push(&self) -> Result<bool> {
+            self.record("push".to_string());
+            Ok(true)
+        }
+
+        fn create_tag(
+            &self,
+            tag: &str,
+            _message: &str,
+            _target: Option<&str>,
+            signing_key: Option<&str>,
+        ) -> Result<()> {
+            self.record(format!("tag {} signed by {}", tag, signing_key.unwrap()));
+            Ok(())
+        }
+
+        fn delete_tag(&self, tag: &str) -> Result<()> {
+            self.record(format!("delete tag {}", tag));
+            Ok(())
+        }
+
+        fn fetch_tags(&self) -> Result<()> {
+            self.record("fetch".to_string());
+            Ok(())
+        }
+
+        fn revert_commit(&self, commit: CommitInfo) -> Result<()> {
+            self.record(format!("revert {}", commit.sha));
+            Ok(())
+        }
+
+        fn list_tags(&self) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
+
+        fn list_remote_tags(&self) -> Result<Vec<String>> {
+            Ok(vec![])
+        }
+
+        fn read_file_at(&self, _rev: &str, _path: &str) -> Result<Option<String>> {
+            Ok(None)
+        }
+    }
+
+    fn create_ctx() -> AppContext {
+        let release = |tag: &str| Release {
+            tag_name: tag.to_string(),
+            ..Default::default()
+        };
+        AppContext::new(vec![release("core@v1.0.0"), release("ui@v2.0.0")]).unwrap()
+    }
+
+    #[test]
+    fn should_plan_and_execute_release_without_prompts() {
+        let io = Rc::new(MockIo::default());
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Minor, "Features")
+            .unwrap();
+        let transaction = rema.execute_release(&ctx).unwrap();
+
+        assert_eq!(
+            *io.calls.borrow(),
+            vec!["commit core@v1.1.0", "push", "create core@v1.1.0", "fetch"]
+        );
+        assert_eq!(transaction.report.releases[0].new_version, "1.1.0");
+        assert_eq!(
+            transaction.report.releases[0].release_url.as_deref(),
+            Some("https://example.com/releases/core@v1.1.0")
+        );
+    }
+
+    #[test]
+    fn should_roll_back_created_releases_when_a_release_fails() {
+        let io = Rc::new(MockIo {
+            failing_tag: "ui@v2.0.1".to_string(),
+            ..Default::default()
+        });
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        rema.plan_release(&mut ctx, "ui", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&ctx);
+
+        assert!(matches!(result, Err(RemaError::ReleaseFailed(_))));
+        assert_eq!(
+            *io.calls.borrow(),
+            vec![
+                "commit core@v1.0.1, ui@v2.0.1",
+                "push",
+                "create core@v1.0.1",
+                "delete core@v1.0.1",
+                "delete tag core@v1.0.1",
+                "revert abc123",
+                "push",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_upload_assets_and_check_them_before_writing() {
+        let io = Rc::new(MockIo::default());
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let asset_globs = vec![
+            "Cargo.toml".parse().unwrap(),
+            "ui=dist/*.zip".parse().unwrap(),
+        ];
+        let mut core_ctx = create_ctx();
+        core_ctx.set_asset_globs(asset_globs.clone());
+        let mut both_ctx = create_ctx();
+        both_ctx.set_asset_globs(asset_globs);
+
+        rema.plan_release(&mut core_ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        let transaction = rema.execute_release(&core_ctx).unwrap();
+        io.calls.borrow_mut().clear();
+        rema.plan_release(&mut both_ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        rema.plan_release(&mut both_ctx, "ui", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&both_ctx);
+
+        let asset_urls = &transaction.report.releases[0].asset_urls;
+        assert_eq!(asset_urls[0], "https://example.com/assets/./Cargo.toml");
+        assert!(asset_urls[1].ends_with("SHA256SUMS"));
+        assert!(matches!(result, Err(RemaError::InvalidInput(msg)) if msg.contains("dist/*.zip")));
+        assert!(io.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn should_find_assets_after_build_hooks_before_committing() {
+        let io = Rc::new(MockIo::default());
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+        ctx.set_config(Config {
+            hooks: HooksConfig {
+                post_write: vec!["build".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        ctx.set_asset_globs(vec!["dist/*.zip".parse().unwrap()]);
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&ctx);
+
+        assert!(matches!(result, Err(RemaError::ReleaseFailed(msg)) if msg.contains("dist/*.zip")));
+        assert_eq!(*io.calls.borrow(), vec!["hook build for core@v1.0.1"]);
+    }
+
+    #[test]
+    fn should_reject_drafts_and_assets_the_forge_lacks_before_writing() {
+        let io = Rc::new(MockIo {
+            tags_only: true,
+            ..Default::default()
+        });
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut draft_ctx = create_ctx();
+        draft_ctx.set_draft(true);
+        let mut assets_ctx = create_ctx();
+        assets_ctx.set_asset_globs(vec!["Cargo.toml".parse().unwrap()]);
+        let mut signed_draft_ctx = create_ctx();
+        signed_draft_ctx.set_draft(true);
+        signed_draft_ctx.set_config(Config {
+            signing: SigningConfig {
+                tags: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        for ctx in [&mut draft_ctx, &mut assets_ctx, &mut signed_draft_ctx] {
+            rema.plan_release(ctx, "core", VersionBump::Patch, "")
+                .unwrap();
+        }
+        let draft = rema.execute_release(&draft_ctx);
+        let assets = rema.execute_release(&assets_ctx);
+        let signed_draft =
+            Rema::new(Rc::new(MockIo::default()), InquirePrompt).execute_release(&signed_draft_ctx);
+
+        assert!(matches!(draft, Err(RemaError::InvalidInput(msg)) if msg.contains("draft")));
+        assert!(matches!(assets, Err(RemaError::InvalidInput(msg)) if msg.contains("assets")));
+        assert!(
+            matches!(signed_draft, Err(RemaError::InvalidInput(msg)) if msg.contains("signed"))
+        );
+        assert!(io.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn should_sign_commit_tags_and_checksums_when_configured() {
+        let io = Rc::new(MockIo::default());
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+        ctx.set_config(Config {
+            signing: SigningConfig {
+                commits: true,
+                tags: true,
+                checksums: true,
+                key: None,
+            },
+            ..Default::default()
+        });
+        ctx.set_asset_globs(vec!["Cargo.toml".parse().unwrap()]);
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        let transaction = rema.execute_release(&ctx).unwrap();
+
+        assert_eq!(
+            *io.calls.borrow(),
+            vec![
+                "commit core@v1.0.1 signed by ~/.ssh/id_ed25519",
+                "push",
+                "sign SHA256SUMS with ~/.ssh/id_ed25519",
+                "tag core@v1.0.1 signed by ~/.ssh/id_ed25519",
+                "create core@v1.0.1 on existing tag",
+                "fetch",
+            ]
+        );
+        assert!(transaction.report.releases[0].asset_urls[2].ends_with("SHA256SUMS.sig"));
+    }
+
+    #[test]
+    fn should_delete_signed_tag_when_its_release_fails() {
+        let io = Rc::new(MockIo {
+            failing_tag: "core@v1.0.1".to_string(),
+            ..Default::default()
+        });
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+        ctx.set_config(Config {
+            signing: SigningConfig {
+                tags: true,
+                key: Some("key::ssh-ed25519 AAAA".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&ctx);
+
+        assert!(matches!(result, Err(RemaError::ReleaseFailed(_))));
+        assert_eq!(
+            *io.calls.borrow(),
+            vec![
+                "commit core@v1.0.1",
+                "push",
+                "tag core@v1.0.1 signed by key::ssh-ed25519 AAAA",
+                "delete tag core@v1.0.1",
+                "revert abc123",
+                "push",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_attach_source_archive_of_release_commit() {
+        let io = Rc::new(MockIo::default());
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+        ctx.set_config(Config {
+            source_archive: true,
+            ..Default::default()
+        });
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        let transaction = rema.execute_release(&ctx).unwrap();
+
+        assert_eq!(io.calls.borrow()[2], "archive . at abc123 as core-v1.0.1");
+        let asset_urls = &transaction.report.releases[0].asset_urls;
+        assert!(asset_urls[0].ends_with("core-v1.0.1.tar.gz"));
+        assert!(asset_urls[1].ends_with("SHA256SUMS"));
+    }
+
+    #[test]
+    fn should_run_hooks_and_roll_back_when_one_fails() {
+        let io = Rc::new(MockIo::default());
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+        ctx.set_config(Config {
+            hooks: HooksConfig {
+                pre_bump: vec!["check".to_string()],
+                post_write: vec!["build".to_string()],
+                pre_commit: vec!["lint".to_string()],
+                post_release: vec!["notify".to_string(), "false".to_string()],
+            },
+            ..Default::default()
+        });
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        rema.plan_release(&mut ctx, "ui", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&ctx);
+
+        assert!(
+            matches!(result, Err(RemaError::ReleaseFailed(msg)) if msg.contains("false exited"))
+        );
+        assert_eq!(
+            *io.calls.borrow(),
+            vec![
+                "hook check for core@v1.0.1",
+                "hook check for ui@v2.0.1",
+                "hook build for core@v1.0.1 ui@v2.0.1",
+                "hook lint for core@v1.0.1 ui@v2.0.1",
+                "commit core@v1.0.1, ui@v2.0.1",
+                "push",
+                "create core@v1.0.1",
+                "create ui@v2.0.1",
+                "fetch",
+                "hook notify for core@v1.0.1",
+                "hook false for core@v1.0.1",
+                "delete core@v1.0.1",
+                "delete ui@v2.0.1",
+                "delete tag core@v1.0.1",
+                "delete tag ui@v2.0.1",
+                "revert abc123",
+                "push",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_restore_written_files_when_pre_commit_hook_fails() {
+        let dir = std::env::temp_dir().join(format!("rema-lib-hook-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("package.json");
+        let original = "{\n  \"name\": \"core\",\n  \"version\": \"1.0.0\"\n}\n";
+        std::fs::write(&path, original).unwrap();
+        let io = Rc::new(MockIo::default());
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+        ctx.set_config(Config {
+            hooks: HooksConfig {
+                pre_commit: vec!["false".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        ctx.set_and_match_local_pkg_files(vec![LocalPackageFiles {
+            name: Some("core".to_string()),
+            package_json: Some(PackageJson {
+                name: Some("core".to_string()),
+                version: Some("1.0.0".to_string()),
+                path: Some(path.to_string_lossy().to_string()),
+                private: false,
+                dependencies: Default::default(),
+                dev_dependencies: Default::default(),
+                peer_dependencies: Default::default(),
+            }),
+            package_lock_json: None,
+            workspace_package_lock_json: None,
+            dir: Some(".".to_string()),
+        }])
+        .unwrap();
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&ctx);
+        let after = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(result, Err(RemaError::ReleaseFailed(_))));
+        assert_eq!(after, original);
+        assert_eq!(*io.calls.borrow(), vec!["hook false for core@v1.0.1"]);
+    }
+
+    #[test]
+    fn should_publish_existing_draft_and_restore_it_on_rollback() {
+        let io = Rc::new(MockIo {
+            failing_tag: "ui@v2.0.1".to_string(),
+            ..Default::default()
+        });
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let release = |tag: &str, is_draft: bool| Release {
+            tag_name: tag.to_string(),
+            is_draft,
+            ..Default::default()
+        };
+        let mut ctx = AppContext::new(vec![
+            release("core@v1.0.0", false),
+            release("core@v1.0.1", true),
+            release("ui@v2.0.0", false),
+        ])
+        .unwrap();
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        rema.plan_release(&mut ctx, "ui", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&ctx);
+
+        assert!(ctx.get_release_targets()[0].publish_draft);
+        assert!(matches!(result, Err(RemaError::ReleaseFailed(_))));
+        assert_eq!(
+            *io.calls.borrow(),
+            vec![
+                "commit core@v1.0.1, ui@v2.0.1",
+                "push",
+                "draft=false core@v1.0.1 at abc123",
+                "draft=true core@v1.0.1",
+                "delete tag core@v1.0.1",
+                "revert abc123",
+                "push",
+            ]
+        );
+    }
+
+    /// Declines to publish drafts and records the tags it was asked about
+    #[derive(Default)]
+    struct DraftPrompt {
+        asked: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Prompt for DraftPrompt {
+        fn select_packages(&self, _: Vec<String>, _: &[String]) -> Result<Vec<String>> {
+            unreachable!()
+        }
+
+        fn select_lockfile_mismatch_resolution(
+            &self,
+            _: &LockfileMismatch,
+        ) -> Result<LockfileMismatchResolution> {
+            unreachable!()
+        }
+
+        fn select_version_bump(&self, _: &AppContext) -> Result<VersionBump> {
+            unreachable!()
+        }
+
+        fn input_release_title(&self, _: &str) -> Result<String> {
+            unreachable!()
+        }
+
+        fn input_release_description(&self, _: &AppContext) -> Result<String> {
+            unreachable!()
+        }
+
+        fn input_release_dependents(&self, _: &[String]) -> Result<bool> {
+            unreachable!()
+        }
+
+        fn input_publish_draft(&self, tag: &str) -> Result<bool> {
+            self.asked.borrow_mut().push(tag.to_string());
+            Ok(false)
+        }
+
+        fn input_confirmation(&self, _: &AppContext) -> Result<bool> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn should_ask_before_publishing_drafts_of_version_group_members() {
+        let release = |tag: &str, is_draft: bool| Release {
+            tag_name: tag.to_string(),
+            is_draft,
+            ..Default::default()
+        };
+        let create_group_ctx = || {
+            let mut ctx = AppContext::new(vec![
+                release("core@v1.0.0", false),
+                release("ui@v1.0.0", false),
+                release("core@v1.0.1", true),
+            ])
+            .unwrap();
+            ctx.set_config(Config {
+                version_groups: vec![vec!["core".to_string(), "ui".to_string()]],
+                ..Default::default()
+            });
+            ctx
+        };
+        let prompt = DraftPrompt::default();
+        let asked = prompt.asked.clone();
+        let rema = Rema::new(Rc::new(MockIo::default()), prompt);
+        let mut ctx = create_group_ctx();
+        let mut planned_ctx = create_group_ctx();
+
+        let release_info = transform::bump_pkg_version(&ctx, "ui", VersionBump::Patch).unwrap();
+        rema.add_release_target_with_group(
+            &mut ctx,
+            ReleaseTarget {
+                pkg_name: "ui".to_string(),
+                previous_version: semver::Version::new(1, 0, 0),
+                title: transform::create_pkg_release_title("ui", &release_info),
+                release_info,
+                description: String::new(),
+                publish_draft: false,
+            },
+            true,
+        )
+        .unwrap();
+        rema.plan_release(&mut planned_ctx, "ui", VersionBump::Patch, "")
+            .unwrap();
+
+        assert_eq!(*asked.borrow(), vec!["core@v1.0.1"]);
+        assert!(!ctx.get_release_targets()[0].publish_draft);
+        assert!(planned_ctx.get_release_targets()[0].publish_draft);
+    }
+
+    #[test]
+    fn should_create_drafts_at_release_commit_and_delete_them_without_tags_on_rollback() {
+        let io = Rc::new(MockIo {
+            failing_tag: "ui@v2.0.1".to_string(),
+            ..Default::default()
+        });
+        let rema = Rema::new(io.clone(), InquirePrompt);
+        let mut ctx = create_ctx();
+        ctx.set_draft(true);
+
+        rema.plan_release(&mut ctx, "core", VersionBump::Patch, "")
+            .unwrap();
+        rema.plan_release(&mut ctx, "ui", VersionBump::Patch, "")
+            .unwrap();
+        let result = rema.execute_release(&ctx);
+
+        assert!(matches!(result, Err(RemaError::ReleaseFailed(_))));
+        assert_eq!(
+            *io.calls.borrow(),
+            vec![
+                "commit core@v1.0.1, ui@v2.0.1",
+                "push",
+                "create draft core@v1.0.1 at abc123",
+                "delete draft core@v1.0.1",
+                "revert abc123",
+                "push",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_only_publish_draft_releases() {
+        let release = |tag: &str, is_draft: bool| Release {
+            tag_name: tag.to_string(),
+            is_draft,
+            ..Default::default()
+        };
+        let io = Rc::new(MockIo {
+            releases: vec![release("core@v1.0.0", false), release("core@v1.1.0", true)],
+            ..Default::default()
+        });
+        let rema = Rema::new(io.clone(), InquirePrompt).with_output(OutputFormat::Json);
+
+        rema.publish("core@v1.1.0").unwrap();
+        let published = rema.publish("core@v1.0.0");
+        let unknown = rema.publish("core@v2.0.0");
+
+        assert_eq!(*io.calls.borrow(), vec!["draft=false core@v1.1.0"]);
+        assert!(matches!(published, Err(RemaError::InvalidInput(_))));
+        assert!(matches!(unknown, Err(RemaError::InvalidInput(_))));
     }
 }
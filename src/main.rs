@@ -1,33 +1,118 @@
 use std::panic;
 
+use clap::{Parser, Subcommand};
 use colorize::AnsiColor;
-use rema::Rema;
+use rema::{
+    AssetGlob, GitTags, InquirePrompt, LatestOverride, OutputFormat, Rema, RemaError, SystemIo,
+};
+
+/// Rema is a tool to help you manage your releases
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Output format. With json, stdout only carries the JSON document.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    /// Release with annotated git tags only, without releases on GitHub or another forge
+    #[arg(long, global = true)]
+    tag_only: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Interactively release one or more packages (default)
+    Release {
+        /// Go through the prompts and report what would be released without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Create the releases as drafts, to publish with `rema publish`
+        #[arg(long)]
+        draft: bool,
+        /// Mark the releases as latest or not, overriding `latest` in rema.json. Repeat it to set
+        /// single packages, e.g. `--latest=false --latest=core=true`.
+        #[arg(long, value_name = "[PACKAGE=]BOOL")]
+        latest: Vec<LatestOverride>,
+        /// Upload the files matching a glob, relative to the package directory, with the
+        /// releases. Fails when nothing matches. Repeat it for more globs.
+        #[arg(long, value_name = "[PACKAGE=]GLOB")]
+        asset: Vec<AssetGlob>,
+    },
+    /// Publish a draft release, which creates its tag
+    Publish {
+        /// Tag of the draft release, e.g. my-package@v1.2.0
+        tag: String,
+    },
+    /// Show every package, its versions and the changes since its latest release
+    Status,
+    /// List inconsistencies between git tags, releases and the package.json at each tag
+    Audit,
+    /// Print the next version of a package for a version bump, without releasing anything
+    Next {
+        /// Package name, empty for a single package repository
+        package: String,
+        /// major, minor, patch, prerelease, local or prerelease:<alpha|beta|rc>[:<base>]
+        #[arg(long)]
+        bump: String,
+        /// Print the tag, including the package name and v-prefix, instead of the version
+        #[arg(long)]
+        tag: bool,
+    },
+}
 
 fn main() {
+    let cli = Cli::parse();
+
+    let output = cli.output;
+    let tag_only = cli.tag_only;
     let result = catch_unwind_silent(|| {
-        Rema::run();
+        let io = if tag_only {
+            SystemIo::with_forge(GitTags)
+        } else {
+            SystemIo::default()
+        };
+        let rema = Rema::new(io, InquirePrompt).with_output(output);
+        match cli.command {
+            None => rema.run(false),
+            Some(Command::Release {
+                dry_run,
+                draft,
+                latest,
+                asset,
+            }) => rema
+                .with_draft(draft)
+                .with_latest(latest)
+                .with_assets(asset)
+                .run(dry_run),
+            Some(Command::Publish { tag }) => rema.publish(&tag),
+            Some(Command::Status) => rema.status(),
+            Some(Command::Audit) => rema.audit(),
+            Some(Command::Next { package, bump, tag }) => rema.next(&package, &bump, tag),
+        }
     });
 
     match result {
-        Ok(_) => {
+        Ok(Ok(())) => {
             std::process::exit(0);
         }
+        Ok(Err(RemaError::Cancelled)) => {
+            eprintln!("\n{}", RemaError::Cancelled);
+            std::process::exit(RemaError::Cancelled.exit_code());
+        }
+        Ok(Err(err)) => {
+            eprintln!("\n{}", format!("Application error: {}", err).red());
+            std::process::exit(err.exit_code());
+        }
+        // Bugs only, every expected failure is a `RemaError`
         Err(err) => {
-            println!("\n");
+            eprintln!("\n");
             if let Some(msg) = err.downcast_ref::<&str>() {
-                if msg.contains("OperationInterrupted") {
-                    println!("Operation interrupted by user");
-                } else {
-                    let error_msg = format!("Application error: {}", msg);
-                    eprintln!("{}", error_msg.red());
-                }
+                eprintln!("{}", format!("Application error: {}", msg).red());
             } else if let Some(msg) = err.downcast_ref::<String>() {
-                if msg.contains("OperationInterrupted") {
-                    println!("Operation interrupted by user");
-                } else {
-                    let error_msg = format!("Application error: {}", msg);
-                    eprintln!("{}", error_msg.red());
-                }
+                eprintln!("{}", format!("Application error: {}", msg).red());
             } else {
                 eprintln!("{}", "An unknown error occurred.".red());
             }
@@ -0,0 +1,84 @@
+use crate::{
+    cli,
+    ctx::AppContext,
+    error::Result,
+    read::{LockfileMismatch, LockfileMismatchResolution},
+    transform::VersionBump,
+};
+
+/// The questions rema asks during an interactive release. `InquirePrompt` asks them in the
+/// terminal; embedders can answer them from code instead, e.g. in a release bot.
+pub trait Prompt {
+    /// Pick the packages to release from labels like `name (3 commits, 5 files changed)`.
+    /// Returns plain package names.
+    fn select_packages(&self, options: Vec<String>, changed_pkgs: &[String])
+        -> Result<Vec<String>>;
+
+    fn select_lockfile_mismatch_resolution(
+        &self,
+        mismatch: &LockfileMismatch,
+    ) -> Result<LockfileMismatchResolution>;
+
+    /// Pick the bump for the selected package of the context
+    fn select_version_bump(&self, ctx: &AppContext) -> Result<VersionBump>;
+
+    /// Edit the release title, which doubles as the tag
+    fn input_release_title(&self, initial_title: &str) -> Result<String>;
+
+    /// Release notes for the selected package, empty to only use generated notes
+    fn input_release_description(&self, ctx: &AppContext) -> Result<String>;
+
+    /// Whether to also patch release the given dependents of the selected packages
+    fn input_release_dependents(&self, dependents: &[String]) -> Result<bool>;
+
+    /// Whether to publish the existing draft release of a tag instead of creating a release
+    fn input_publish_draft(&self, tag: &str) -> Result<bool>;
+
+    /// Final go-ahead for the release targets of the context
+    fn input_confirmation(&self, ctx: &AppContext) -> Result<bool>;
+}
+
+/// Asks in the terminal
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InquirePrompt;
+
+impl Prompt for InquirePrompt {
+    fn select_packages(
+        &self,
+        options: Vec<String>,
+        changed_pkgs: &[String],
+    ) -> Result<Vec<String>> {
+        cli::select_pkg_names(options, changed_pkgs)
+    }
+
+    fn select_lockfile_mismatch_resolution(
+        &self,
+        mismatch: &LockfileMismatch,
+    ) -> Result<LockfileMismatchResolution> {
+        cli::select_lockfile_mismatch_resolution(mismatch)
+    }
+
+    fn select_version_bump(&self, ctx: &AppContext) -> Result<VersionBump> {
+        cli::select_version_bump(ctx)
+    }
+
+    fn input_release_title(&self, initial_title: &str) -> Result<String> {
+        cli::input_release_title(initial_title)
+    }
+
+    fn input_release_description(&self, ctx: &AppContext) -> Result<String> {
+        cli::input_release_description(ctx)
+    }
+
+    fn input_release_dependents(&self, dependents: &[String]) -> Result<bool> {
+        cli::input_release_dependents(dependents)
+    }
+
+    fn input_publish_draft(&self, tag: &str) -> Result<bool> {
+        cli::input_publish_draft(tag)
+    }
+
+    fn input_confirmation(&self, ctx: &AppContext) -> Result<bool> {
+        cli::input_confirmation(ctx)
+    }
+}
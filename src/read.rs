@@ -1,7 +1,14 @@
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::AssetPattern,
+    error::{RemaError, Result},
+};
 
 /// Struct to hold relevant package.json fields
 #[derive(Deserialize, Debug, Clone)]
@@ -9,6 +16,32 @@ pub struct PackageJson {
     pub name: Option<String>,
     pub version: Option<String>,
     pub path: Option<String>,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub dependencies: HashMap<String, String>,
+    #[serde(default, rename = "devDependencies")]
+    pub dev_dependencies: HashMap<String, String>,
+    #[serde(default, rename = "peerDependencies")]
+    pub peer_dependencies: HashMap<String, String>,
+}
+
+/// Root package.json fields that declare a workspace
+#[derive(Deserialize, Debug)]
+struct RootPackageJson {
+    workspaces: Option<WorkspacesField>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum WorkspacesField {
+    /// npm and yarn, e.g. `"workspaces": ["packages/*"]`
+    List(Vec<String>),
+    /// yarn config with hoisting options, e.g. `"workspaces": { "packages": ["packages/*"] }`
+    Config {
+        #[serde(default)]
+        packages: Vec<String>,
+    },
 }
 
 /// Struct to hold relevant package-lock.json fields
@@ -17,6 +50,25 @@ pub struct PackageLockJson {
     pub name: Option<String>,
     pub version: Option<String>,
     pub path: Option<String>,
+    /// Key of the package under `packages` when this is the root lockfile of a workspace,
+    /// e.g. `packages/megatron`. `None` when the lockfile sits next to the package.json.
+    #[serde(skip)]
+    pub workspace_key: Option<String>,
+}
+
+/// Entry under `packages` in a lockfile v2/v3
+#[derive(Deserialize, Debug, Clone)]
+struct PackageLockEntry {
+    version: Option<String>,
+}
+
+/// Fields of package-lock.json needed while scanning, including the `packages` entries
+#[derive(Deserialize, Debug)]
+struct RawPackageLockJson {
+    name: Option<String>,
+    version: Option<String>,
+    #[serde(default)]
+    packages: HashMap<String, PackageLockEntry>,
 }
 
 /// Struct to hold package.json and package-lock.json
@@ -25,104 +77,499 @@ pub struct LocalPackageFiles {
     pub name: Option<String>,
     pub package_json: Option<PackageJson>,
     pub package_lock_json: Option<PackageLockJson>,
+    /// Root lockfile of the npm workspace this package belongs to, if any
+    pub workspace_package_lock_json: Option<PackageLockJson>,
+    /// Directory of the package relative to the scanned root, `.` for the root itself
+    pub dir: Option<String>,
+}
+
+/// A package-lock.json that records a different version than its package.json
+#[derive(Debug, Clone)]
+pub struct LockfileMismatch {
+    pub pkg_name: Option<String>,
+    pub package_json: PackageJson,
+    pub package_lock_json: PackageLockJson,
+}
+
+/// How to deal with a lockfile that is out of sync with its package.json
+#[derive(Debug, Clone, PartialEq)]
+pub enum LockfileMismatchResolution {
+    /// Write the package.json version into the lockfile
+    Resync,
+    /// Leave the files alone and exclude the package from the release selection
+    Skip,
+}
+
+/// Local package files found in the working directory
+#[derive(Debug, Clone)]
+pub struct LocalScan {
+    pub pkg_files: Vec<LocalPackageFiles>,
+    /// Workspace packages marked `"private": true`, only used to update their dependency ranges
+    pub private_pkg_files: Vec<LocalPackageFiles>,
+    /// Lockfiles that are not attached to `pkg_files` because their version doesn't match
+    pub lockfile_mismatches: Vec<LockfileMismatch>,
+}
+
+/// Package managers whose lockfile rema can regenerate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+}
+
+impl PackageManager {
+    pub fn lockfile_name(&self) -> &'static str {
+        match self {
+            PackageManager::Npm => "package-lock.json",
+            PackageManager::Pnpm => "pnpm-lock.yaml",
+            PackageManager::Yarn => "yarn.lock",
+        }
+    }
 }
 
-pub fn find_local_pkg_files() -> Option<Vec<LocalPackageFiles>> {
-    let current_dir = env::current_dir().expect("Failed to get current directory");
+/// The package manager of the current directory and the path of its lockfile, picked by the
+/// lockfile found there
+pub fn find_root_lockfile() -> Result<Option<(PackageManager, PathBuf)>> {
+    let current_dir = env::current_dir()
+        .map_err(|e| RemaError::Manifest(format!("Failed to get current directory: {}", e)))?;
 
-    println!("Scanning for package.json files in {:?}", current_dir);
+    Ok(find_lockfile(&current_dir))
+}
+
+fn find_lockfile(dir: &Path) -> Option<(PackageManager, PathBuf)> {
+    [
+        PackageManager::Npm,
+        PackageManager::Pnpm,
+        PackageManager::Yarn,
+    ]
+    .into_iter()
+    .map(|manager| (manager, dir.join(manager.lockfile_name())))
+    .find(|(_, path)| path.is_file())
+}
+
+/// Find the releasable packages in the current directory.
+///
+/// If the directory is the root of a workspace (`workspaces` in package.json or a
+/// pnpm-workspace.yaml), only the workspace packages that aren't `"private": true` are included.
+/// The private ones are returned apart so their dependency ranges can be updated. Otherwise every
+/// package.json in the tree is picked up.
+pub fn find_local_pkg_files() -> Result<Option<LocalScan>> {
+    let current_dir = env::current_dir()
+        .map_err(|e| RemaError::Manifest(format!("Failed to get current directory: {}", e)))?;
+
+    Ok(scan_dir(&current_dir))
+}
 
+fn scan_dir(dir: &Path) -> Option<LocalScan> {
     let mut results = Vec::<LocalPackageFiles>::new();
+    let mut private_results = Vec::<LocalPackageFiles>::new();
+    let mut mismatches = Vec::<LockfileMismatch>::new();
 
-    scan_for_package_json(&current_dir, &mut results);
-    scan_for_package_lock_json(&current_dir, &mut results);
+    match read_workspace_patterns(dir) {
+        Some(patterns) => {
+            eprintln!("Scanning for workspace packages in {:?}", dir);
+            scan_for_workspace_package_json(dir, &patterns, &mut results, &mut private_results);
+        }
+        None => {
+            eprintln!("Scanning for package.json files in {:?}", dir);
+            scan_for_package_json(dir, dir, &mut results);
+        }
+    }
+    scan_for_package_lock_json(dir, &mut results, &mut mismatches);
 
     if results.is_empty() {
         None
     } else {
-        Some(results)
+        Some(LocalScan {
+            pkg_files: results,
+            private_pkg_files: private_results,
+            lockfile_mismatches: mismatches,
+        })
     }
 }
 
 /// Recursively scans directories for package.json files, skipping node_modules
-fn scan_for_package_json(dir: &Path, results: &mut Vec<LocalPackageFiles>) {
+fn scan_for_package_json(root: &Path, dir: &Path, results: &mut Vec<LocalPackageFiles>) {
     if let Ok(entries) = fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
 
-            if path
-                .file_name()
-                .map_or(false, |name| name == "node_modules")
-            {
+            if path.file_name().is_some_and(|name| name == "node_modules") {
                 continue;
             }
 
             if path.is_dir() {
                 // Recurse into subdirectories
-                scan_for_package_json(&path, results);
-            } else if path
-                .file_name()
-                .map_or(false, |name| name == "package.json")
-            {
+                scan_for_package_json(root, &path, results);
+            } else if path.file_name().is_some_and(|name| name == "package.json") {
                 // Parse package.json
                 if let Some(package_json) = parse_package_json(&path) {
-                    let pkg_files = LocalPackageFiles {
-                        name: package_json.name.clone(),
-                        package_json: Some(package_json),
-                        package_lock_json: None,
-                    };
-                    results.push(pkg_files);
+                    results.push(create_local_pkg_files(root, dir, package_json));
                 }
             }
         }
     }
 }
 
-/// Recursively scans directories for package-lock.json files, skipping node_modules
-/// Will only add them if a package.json that matches the name is found
-fn scan_for_package_lock_json(dir: &Path, results: &mut Vec<LocalPackageFiles>) {
+/// Scans the directories matched by the workspace patterns for package.json files, keeping
+/// private packages apart
+fn scan_for_workspace_package_json(
+    root: &Path,
+    patterns: &[String],
+    results: &mut Vec<LocalPackageFiles>,
+    private_results: &mut Vec<LocalPackageFiles>,
+) {
+    for dir in find_workspace_dirs(root, patterns) {
+        if let Some(package_json) = parse_package_json(&dir.join("package.json")) {
+            if package_json.private {
+                private_results.push(create_local_pkg_files(root, &dir, package_json));
+                continue;
+            }
+            results.push(create_local_pkg_files(root, &dir, package_json));
+        }
+    }
+}
+
+fn create_local_pkg_files(root: &Path, dir: &Path, package_json: PackageJson) -> LocalPackageFiles {
+    LocalPackageFiles {
+        name: package_json.name.clone(),
+        package_json: Some(package_json),
+        package_lock_json: None,
+        workspace_package_lock_json: None,
+        dir: Some(relative_dir(root, dir)),
+    }
+}
+
+/// Directory relative to root with `/` separators, `.` for the root itself
+fn relative_dir(root: &Path, dir: &Path) -> String {
+    let segments: Vec<String> = dir
+        .strip_prefix(root)
+        .unwrap_or(dir)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    if segments.is_empty() {
+        ".".to_string()
+    } else {
+        segments.join("/")
+    }
+}
+
+/// Read the workspace package patterns of the root, from pnpm-workspace.yaml or the
+/// `workspaces` field of package.json. Returns `None` if the root isn't a workspace.
+fn read_workspace_patterns(root: &Path) -> Option<Vec<String>> {
+    if let Ok(content) = fs::read_to_string(root.join("pnpm-workspace.yaml")) {
+        return Some(parse_pnpm_workspace_packages(&content));
+    }
+
+    let content = fs::read_to_string(root.join("package.json")).ok()?;
+    let root_package_json: RootPackageJson = serde_json::from_str(&content).ok()?;
+
+    match root_package_json.workspaces? {
+        WorkspacesField::List(packages) => Some(packages),
+        WorkspacesField::Config { packages } => Some(packages),
+    }
+}
+
+/// Read the `packages` list of a pnpm-workspace.yaml
+fn parse_pnpm_workspace_packages(content: &str) -> Vec<String> {
+    let mut packages = Vec::new();
+    let mut in_packages = false;
+
+    for line in content.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim_end();
+        let trimmed = line.trim_start();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // A new top-level key ends the packages list
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = trimmed.starts_with("packages:");
+            continue;
+        }
+
+        if in_packages {
+            if let Some(pattern) = trimmed.strip_prefix('-') {
+                let pattern = pattern.trim().trim_matches(|c| c == '\'' || c == '"');
+                if !pattern.is_empty() {
+                    packages.push(pattern.to_string());
+                }
+            }
+        }
+    }
+
+    packages
+}
+
+/// Find the directories containing a package.json that match the workspace patterns. Patterns
+/// starting with `!` exclude directories.
+fn find_workspace_dirs(root: &Path, patterns: &[String]) -> Vec<PathBuf> {
+    let (excludes, includes): (Vec<&String>, Vec<&String>) =
+        patterns.iter().partition(|p| p.starts_with('!'));
+
+    let mut candidates = Vec::new();
+    collect_pkg_dirs(root, &mut candidates);
+
+    let mut dirs: Vec<PathBuf> = candidates
+        .into_iter()
+        .filter(|dir| {
+            let rel = relative_dir(root, dir);
+            includes.iter().any(|p| glob_match(p, &rel))
+                && !excludes.iter().any(|p| glob_match(&p[1..], &rel))
+        })
+        .collect();
+
+    dirs.sort();
+    dirs
+}
+
+/// Recursively collect every directory below root that contains a package.json, skipping
+/// node_modules and the root itself
+fn collect_pkg_dirs(dir: &Path, results: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if !path.is_dir() || path.file_name().is_some_and(|name| name == "node_modules") {
+                continue;
+            }
+
+            if path.join("package.json").is_file() {
+                results.push(path.clone());
+            }
+            collect_pkg_dirs(&path, results);
+        }
+    }
+}
+
+/// Files below a package directory matching the asset globs, sorted. Fails when a required glob
+/// matches no file.
+pub fn find_release_assets(dir: &Path, patterns: &[AssetPattern]) -> Result<Vec<PathBuf>> {
+    let mut assets = Vec::new();
+    for pattern in patterns {
+        // Only walk below the part of the glob without wildcards
+        let glob = pattern.glob.trim_start_matches("./");
+        let base: Vec<&str> = glob
+            .split('/')
+            .take_while(|segment| !segment.contains('*'))
+            .collect();
+        let base = dir.join(base.join("/"));
+
+        let mut files = Vec::new();
+        if base.is_file() {
+            files.push(base);
+        } else {
+            collect_files(&base, &mut files);
+        }
+        let matches: Vec<PathBuf> = files
+            .into_iter()
+            .filter(|file| glob_match(glob, &relative_dir(dir, file)))
+            .collect();
+
+        if matches.is_empty() && !pattern.optional {
+            return Err(RemaError::InvalidInput(format!(
+                "No release asset matches {} in {}",
+                pattern.glob,
+                dir.display()
+            )));
+        }
+        assets.extend(matches);
+    }
+
+    assets.sort();
+    assets.dedup();
+    Ok(assets)
+}
+
+/// Hex encoded SHA-256 digest of a file
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).map_err(|e| {
+        RemaError::Manifest(format!("Failed to read asset {}: {}", path.display(), e))
+    })?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| {
+        RemaError::Manifest(format!("Failed to read asset {}: {}", path.display(), e))
+    })?;
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Recursively collect every file below dir, skipping node_modules and .git
+fn collect_files(dir: &Path, results: &mut Vec<PathBuf>) {
     if let Ok(entries) = fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
 
             if path
                 .file_name()
-                .map_or(false, |name| name == "node_modules")
+                .is_some_and(|name| name == "node_modules" || name == ".git")
             {
                 continue;
             }
 
+            if path.is_dir() {
+                collect_files(&path, results);
+            } else {
+                results.push(path);
+            }
+        }
+    }
+}
+
+/// Match a relative directory against a workspace glob. `*` matches within one path segment
+/// and `**` matches any number of segments.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            match_segments(&pattern[1..], path)
+                || (!path.is_empty() && match_segments(pattern, &path[1..]))
+        }
+        (Some(p), Some(s)) => match_segment(p, s) && match_segments(&pattern[1..], &path[1..]),
+        _ => false,
+    }
+}
+
+fn match_segment(pattern: &str, segment: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == segment,
+        Some((prefix, rest)) => {
+            let Some(remaining) = segment.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=remaining.len())
+                .filter(|i| remaining.is_char_boundary(*i))
+                .any(|i| match_segment(rest, &remaining[i..]))
+        }
+    }
+}
+
+/// Recursively scans directories for package-lock.json files, skipping node_modules
+/// Will only add them if a package.json that matches the name is found, either next to the
+/// lockfile or, for workspace root lockfiles, listed under `packages`. Lockfiles whose version
+/// differs from the package.json are collected in `mismatches` instead.
+fn scan_for_package_lock_json(
+    dir: &Path,
+    results: &mut Vec<LocalPackageFiles>,
+    mismatches: &mut Vec<LockfileMismatch>,
+) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path.file_name().is_some_and(|name| name == "node_modules") {
+                continue;
+            }
+
             if path.is_dir() {
                 // Recurse into subdirectories
-                scan_for_package_lock_json(&path, results);
+                scan_for_package_lock_json(&path, results, mismatches);
             } else if path
                 .file_name()
-                .map_or(false, |name| name == "package-lock.json")
+                .is_some_and(|name| name == "package-lock.json")
             {
                 // Parse package-lock.json
-                if let Some(package_lock_json) = parse_package_lock_json(&path) {
-                    let name = package_lock_json.name.clone();
+                if let Some(raw) = parse_package_lock_json(&path) {
+                    let path_str = path.to_string_lossy().to_string();
+                    let name = raw.name.clone();
                     if let Some(pkg_files) = results.iter_mut().find(|x| x.name == name) {
-                        if pkg_files
-                            .package_json
-                            .as_ref()
-                            .and_then(|pj| pj.version.clone())
-                            == package_lock_json.version
-                        {
-                            pkg_files.package_lock_json = Some(package_lock_json);
+                        let package_lock_json = PackageLockJson {
+                            name: raw.name.clone(),
+                            version: raw.version.clone(),
+                            path: Some(path_str.clone()),
+                            workspace_key: None,
+                        };
+                        if let Some(mismatch) = find_lock_mismatch(pkg_files, &package_lock_json) {
+                            mismatches.push(mismatch);
                         } else {
-                            panic!(
-                                "Found package-lock.json with no matching package.json or version. {:?}",
-                                pkg_files.package_json.as_ref().unwrap().path.clone().unwrap_or_default()
-                            )
+                            pkg_files.package_lock_json = Some(package_lock_json);
                         }
                     }
+
+                    match_workspace_lock_entries(dir, &path_str, &raw, results, mismatches);
                 }
             }
         }
     }
 }
 
+/// Match the `packages/<name>` entries of a workspace root lockfile with the scanned package.json
+/// files that live in those directories.
+fn match_workspace_lock_entries(
+    dir: &Path,
+    lock_path: &str,
+    raw: &RawPackageLockJson,
+    results: &mut [LocalPackageFiles],
+    mismatches: &mut Vec<LockfileMismatch>,
+) {
+    for (key, entry) in raw.packages.iter() {
+        if key.is_empty() || key.contains("node_modules") {
+            continue;
+        }
+
+        let manifest_path = dir.join(key).join("package.json");
+        let matched = results.iter_mut().find(|x| {
+            x.package_json
+                .as_ref()
+                .and_then(|pj| pj.path.as_ref())
+                .is_some_and(|p| Path::new(p) == manifest_path)
+        });
+
+        if let Some(pkg_files) = matched {
+            let package_lock_json = PackageLockJson {
+                name: pkg_files.name.clone(),
+                version: entry.version.clone(),
+                path: Some(lock_path.to_string()),
+                workspace_key: Some(key.clone()),
+            };
+            // Workspace entries only record a version once npm has seen one in the manifest
+            let mismatch = package_lock_json
+                .version
+                .as_ref()
+                .and_then(|_| find_lock_mismatch(pkg_files, &package_lock_json));
+
+            if let Some(mismatch) = mismatch {
+                mismatches.push(mismatch);
+            } else {
+                pkg_files.workspace_package_lock_json = Some(package_lock_json);
+            }
+        }
+    }
+}
+
+fn find_lock_mismatch(
+    pkg_files: &LocalPackageFiles,
+    package_lock_json: &PackageLockJson,
+) -> Option<LockfileMismatch> {
+    let package_json = pkg_files.package_json.as_ref()?;
+
+    if package_json.version == package_lock_json.version {
+        return None;
+    }
+
+    Some(LockfileMismatch {
+        pkg_name: pkg_files.name.clone(),
+        package_json: package_json.clone(),
+        package_lock_json: package_lock_json.clone(),
+    })
+}
+
 /// Function to parse package.json
 fn parse_package_json(path: &Path) -> Option<PackageJson> {
     let content = fs::read_to_string(path).ok()?;
@@ -132,9 +579,233 @@ fn parse_package_json(path: &Path) -> Option<PackageJson> {
 }
 
 /// Function to parse package-lock.json
-fn parse_package_lock_json(path: &Path) -> Option<PackageLockJson> {
+fn parse_package_lock_json(path: &Path) -> Option<RawPackageLockJson> {
     let content = fs::read_to_string(path).ok()?;
-    let mut package_lock_json: PackageLockJson = serde_json::from_str(&content).ok()?;
-    package_lock_json.path = Some(path.to_string_lossy().to_string());
-    Some(package_lock_json)
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_dir(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = env::temp_dir().join(format!("rema-read-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for (path, contents) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn should_match_workspace_globs() {
+        assert!(glob_match("packages/*", "packages/megatron"));
+        assert!(glob_match("./packages/*/", "packages/megatron"));
+        assert!(!glob_match("packages/*", "packages/megatron/fixtures"));
+        assert!(glob_match("packages/**", "packages/megatron/fixtures"));
+        assert!(glob_match("apps/web-*", "apps/web-admin"));
+        assert!(!glob_match("apps/web-*", "apps/api"));
+        assert!(glob_match("tools/cli", "tools/cli"));
+        assert!(!glob_match("packages/*", "."));
+    }
+
+    #[test]
+    fn should_find_release_assets() {
+        let dir = create_test_dir(
+            "assets",
+            &[
+                ("dist/core-1.0.0.tgz", ""),
+                ("dist/bin/core-linux", ""),
+                ("dist/bin/core-macos", ""),
+                ("node_modules/dep/dist/dep.tgz", ""),
+            ],
+        );
+        let pattern = |glob: &str, optional| AssetPattern {
+            glob: glob.to_string(),
+            optional,
+        };
+
+        let assets = find_release_assets(
+            &dir,
+            &[
+                pattern("dist/bin/*", false),
+                pattern("**/*.tgz", false),
+                pattern("./dist/core-1.0.0.tgz", false),
+                pattern("dist/*.map", true),
+            ],
+        )
+        .unwrap();
+        let missing = find_release_assets(&dir, &[pattern("dist/*.zip", false)]);
+
+        assert_eq!(
+            assets,
+            vec![
+                dir.join("dist/bin/core-linux"),
+                dir.join("dist/bin/core-macos"),
+                dir.join("dist/core-1.0.0.tgz"),
+            ]
+        );
+        assert!(missing.is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_pick_package_manager_by_lockfile() {
+        let pnpm = create_test_dir("pnpm", &[("pnpm-lock.yaml", ""), ("package.json", "{}")]);
+        let none = create_test_dir("no-lockfile", &[("package.json", "{}")]);
+
+        assert_eq!(
+            find_lockfile(&pnpm),
+            Some((PackageManager::Pnpm, pnpm.join("pnpm-lock.yaml")))
+        );
+        assert_eq!(find_lockfile(&none), None);
+        fs::remove_dir_all(&pnpm).unwrap();
+        fs::remove_dir_all(&none).unwrap();
+    }
+
+    #[test]
+    fn should_hash_files_with_sha256() {
+        let dir = create_test_dir("sha256", &[("abc.txt", "abc")]);
+
+        let digest = sha256_file(&dir.join("abc.txt")).unwrap();
+
+        assert_eq!(
+            digest,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn should_parse_pnpm_workspace_packages() {
+        let content = r#"
+# workspace config
+packages:
+  - 'packages/*'
+  - "apps/**" # all apps
+  - '!**/test/**'
+catalog:
+  react: ^18.0.0
+"#;
+
+        assert_eq!(
+            parse_pnpm_workspace_packages(content),
+            vec!["packages/*", "apps/**", "!**/test/**"]
+        );
+    }
+
+    #[test]
+    fn should_only_scan_public_workspace_packages() {
+        let dir = create_test_dir(
+            "workspace-scan",
+            &[
+                (
+                    "package.json",
+                    r#"{ "name": "zoo", "private": true, "workspaces": { "packages": ["packages/*", "!packages/internal"] } }"#,
+                ),
+                (
+                    "packages/tiger/package.json",
+                    r#"{ "name": "tiger", "version": "1.0.0" }"#,
+                ),
+                (
+                    "packages/tiger/fixtures/package.json",
+                    r#"{ "name": "fixture", "version": "0.0.0" }"#,
+                ),
+                (
+                    "packages/lion/package.json",
+                    r#"{ "name": "lion", "version": "1.0.0", "private": true }"#,
+                ),
+                (
+                    "packages/internal/package.json",
+                    r#"{ "name": "internal", "version": "1.0.0" }"#,
+                ),
+                (
+                    "examples/demo/package.json",
+                    r#"{ "name": "demo", "version": "1.0.0" }"#,
+                ),
+            ],
+        );
+
+        let scan = scan_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(scan.pkg_files.len(), 1);
+        assert_eq!(scan.pkg_files[0].name, Some("tiger".to_string()));
+        assert_eq!(scan.pkg_files[0].dir, Some("packages/tiger".to_string()));
+        assert_eq!(scan.private_pkg_files.len(), 1);
+        assert_eq!(scan.private_pkg_files[0].name, Some("lion".to_string()));
+    }
+
+    #[test]
+    fn should_report_lockfile_mismatch_instead_of_panicking() {
+        let dir = create_test_dir(
+            "mismatch",
+            &[
+                ("package.json", r#"{ "name": "tiger", "version": "1.0.1" }"#),
+                (
+                    "package-lock.json",
+                    r#"{ "name": "tiger", "version": "1.0.0" }"#,
+                ),
+            ],
+        );
+
+        let scan = scan_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(scan.pkg_files.len(), 1);
+        assert!(scan.pkg_files[0].package_lock_json.is_none());
+        assert_eq!(scan.lockfile_mismatches.len(), 1);
+        assert_eq!(
+            scan.lockfile_mismatches[0].package_lock_json.version,
+            Some("1.0.0".to_string())
+        );
+    }
+
+    #[test]
+    fn should_match_workspace_root_lockfile_entries() {
+        let dir = create_test_dir(
+            "workspace",
+            &[
+                (
+                    "package.json",
+                    r#"{ "name": "zoo", "version": "1.0.0", "workspaces": ["packages/*"] }"#,
+                ),
+                (
+                    "package-lock.json",
+                    r#"{
+                        "name": "zoo",
+                        "version": "1.0.0",
+                        "packages": {
+                            "": { "name": "zoo", "version": "1.0.0" },
+                            "packages/tiger": { "version": "2.0.0" },
+                            "node_modules/tiger": { "link": true, "resolved": "packages/tiger" }
+                        }
+                    }"#,
+                ),
+                (
+                    "packages/tiger/package.json",
+                    r#"{ "name": "tiger", "version": "2.0.0" }"#,
+                ),
+            ],
+        );
+
+        let scan = scan_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let tiger = scan
+            .pkg_files
+            .iter()
+            .find(|pkg| pkg.name == Some("tiger".to_string()))
+            .unwrap();
+        let workspace_lock = tiger.workspace_package_lock_json.as_ref().unwrap();
+
+        assert!(tiger.package_lock_json.is_none());
+        assert_eq!(
+            workspace_lock.workspace_key,
+            Some("packages/tiger".to_string())
+        );
+        assert!(scan.lockfile_mismatches.is_empty());
+    }
 }
@@ -1,7 +1,15 @@
 use semver::{BuildMetadata, Prerelease, Version};
-
-use crate::{ctx::AppContext, gh::Release, read::LocalPackageFiles};
-use std::{collections::HashMap, error::Error};
+use serde::Serialize;
+
+use crate::{
+    config::AssetPattern,
+    ctx::AppContext,
+    error::{RemaError, Result},
+    forge::{CreatedRelease, Release},
+    git::ChangeSummary,
+    read::LocalPackageFiles,
+};
+use std::{collections::HashMap, str::FromStr};
 
 #[derive(Debug, Clone)]
 pub enum PreReleaseType {
@@ -36,14 +44,277 @@ pub struct ReleaseInfo {
     pub local_only: bool,
 }
 
+/// A package release planned in this run
+#[derive(Debug, Clone)]
+pub struct ReleaseTarget {
+    pub pkg_name: String,
+    /// Latest version before this release
+    pub previous_version: Version,
+    pub release_info: ReleaseInfo,
+    pub title: String,
+    pub description: String,
+    /// Publish the existing draft release of the tag instead of creating a release
+    pub publish_draft: bool,
+}
+
+/// Overview of one package, as shown by `rema status`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PkgStatus {
+    pub name: String,
+    pub latest_stable: Option<String>,
+    pub latest_pre: Option<String>,
+    /// Version in the local package.json
+    pub local_version: Option<String>,
+    pub local_only: bool,
+    /// When the latest release was published, as reported by GitHub
+    pub last_released_at: Option<String>,
+    /// Commits touching the package directory since its latest release
+    pub commits_behind: Option<usize>,
+    pub files_changed: Option<usize>,
+}
+
+/// Computed next version of a package, as printed by `rema next`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NextVersion {
+    pub package: String,
+    pub current_version: String,
+    pub version: String,
+    pub tag: String,
+}
+
+/// Outcome of one package release, or what it would be in a dry run
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseResult {
+    pub package: String,
+    pub previous_version: String,
+    pub new_version: String,
+    pub tag: String,
+    /// URL of the created release, none in a dry run
+    pub release_url: Option<String>,
+    /// Whether the release is marked as latest, none to leave it to the forge
+    pub latest: Option<bool>,
+    /// Download URLs of the uploaded assets
+    pub asset_urls: Vec<String>,
+    /// Local files in which the package's own version changes
+    pub files_changed: Vec<String>,
+}
+
+/// Outcome of a release run
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseReport {
+    pub dry_run: bool,
+    /// Releases were created as drafts
+    pub draft: bool,
+    pub releases: Vec<ReleaseResult>,
+    /// Every local file changed, including dependency range updates of dependents
+    pub files_changed: Vec<String>,
+}
+
+/// A `--latest` flag, either `<true|false>` for every package or `<package>=<true|false>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatestOverride {
+    /// None for every package
+    pub package: Option<String>,
+    pub latest: bool,
+}
+
+impl FromStr for LatestOverride {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let (package, latest) = match value.rsplit_once('=') {
+            Some((package, latest)) => (Some(package.to_string()), latest),
+            None => (None, value),
+        };
+        let latest = latest
+            .parse()
+            .map_err(|_| format!("Expected true or false, got {}", latest))?;
+
+        Ok(Self { package, latest })
+    }
+}
+
+/// An `--asset` flag, either a glob for every package or `<package>=<glob>`. Globs are relative to
+/// the package directory and required.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetGlob {
+    /// None for every package
+    pub package: Option<String>,
+    pub glob: String,
+}
+
+impl FromStr for AssetGlob {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let (package, glob) = match value.split_once('=') {
+            Some((package, glob)) => (Some(package.to_string()), glob),
+            None => (None, value),
+        };
+        if glob.is_empty() {
+            return Err("Expected a glob, e.g. dist/*.tgz".to_string());
+        }
+
+        Ok(Self {
+            package,
+            glob: glob.to_string(),
+        })
+    }
+}
+
+/// A draft release that was published, as printed by `rema publish`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishedRelease {
+    pub tag: String,
+    pub release_url: Option<String>,
+}
+
+/// Kind of inconsistency found by `rema audit`
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditIssue {
+    /// A release tag with no release
+    TagWithoutRelease,
+    /// A published release whose tag isn't in the local repository
+    MissingLocalTag,
+    /// A published release whose tag isn't on `origin`
+    MissingRemoteTag,
+    /// A release that was never published
+    DraftRelease,
+    /// A release whose pre-release flag disagrees with the version of its tag
+    PrereleaseMismatch,
+    /// The package.json at the tag has another version than the tag
+    ManifestMismatch,
+}
+
+/// One inconsistency between tags, releases and package manifests
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFinding {
+    pub issue: AuditIssue,
+    pub tag: String,
+    pub detail: String,
+}
+
+/// Dependency fields of package.json that can reference other local packages
+pub const DEPENDENCY_FIELDS: [&str; 3] = ["dependencies", "devDependencies", "peerDependencies"];
+
+/// Which local packages depend on which, built from the discovered package.json files
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    /// Package name mapped to the names of the local packages that depend on it
+    dependents: HashMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    pub fn new(local_pkg_files: &[LocalPackageFiles]) -> Self {
+        let local_names: Vec<&String> = local_pkg_files
+            .iter()
+            .filter_map(|pkg| pkg.name.as_ref())
+            .collect();
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+
+        for pkg in local_pkg_files {
+            let (Some(name), Some(package_json)) = (&pkg.name, &pkg.package_json) else {
+                continue;
+            };
+
+            let deps = package_json
+                .dependencies
+                .keys()
+                .chain(package_json.dev_dependencies.keys())
+                .chain(package_json.peer_dependencies.keys());
+
+            for dep in deps {
+                if dep == name || !local_names.contains(&dep) {
+                    continue;
+                }
+                let entry = dependents.entry(dep.clone()).or_default();
+                if !entry.contains(name) {
+                    entry.push(name.clone());
+                }
+            }
+        }
+
+        for names in dependents.values_mut() {
+            names.sort();
+        }
+
+        Self { dependents }
+    }
+
+    /// Local packages that directly depend on the given package
+    pub fn get_dependents(&self, pkg_name: &str) -> Vec<String> {
+        self.dependents.get(pkg_name).cloned().unwrap_or_default()
+    }
+
+    /// Local packages that depend on the given package, directly or through other local packages
+    pub fn get_transitive_dependents(&self, pkg_name: &str) -> Vec<String> {
+        let mut found: Vec<String> = Vec::new();
+        let mut queue = vec![pkg_name.to_string()];
+
+        while let Some(current) = queue.pop() {
+            for dependent in self.get_dependents(&current) {
+                if dependent != pkg_name && !found.contains(&dependent) {
+                    found.push(dependent.clone());
+                    queue.push(dependent);
+                }
+            }
+        }
+
+        found.sort();
+        found
+    }
+}
+
+/// Point a dependency range at a new version while keeping its `^`, `~` or `workspace:` protocol.
+/// Returns `None` when the range should be left as is, e.g. `workspace:*`, `*` or a complex range.
+/// A pre-release only replaces a range that already points at a pre-release, so stable dependents
+/// don't opt into it. A `v` before the version is kept.
+pub fn update_dependency_range(range: &str, version: &Version) -> Option<String> {
+    if let Some(workspace_range) = range.strip_prefix("workspace:") {
+        // `workspace:*`, `workspace:^` and `workspace:~` are resolved when publishing
+        if matches!(workspace_range, "*" | "^" | "~" | "") {
+            return None;
+        }
+        return update_dependency_range(workspace_range, version)
+            .map(|updated| format!("workspace:{}", updated));
+    }
+
+    let operator = ["^", "~", ">=", "="]
+        .into_iter()
+        .find(|op| range.starts_with(op))
+        .unwrap_or("");
+    let current = &range[operator.len()..];
+    let prefix = if current.starts_with('v') { "v" } else { "" };
+
+    let current = Version::parse(&current[prefix.len()..]).ok()?;
+    if !version.pre.is_empty() && current.pre.is_empty() {
+        return None;
+    }
+
+    let updated = format!("{}{}{}", operator, prefix, version);
+    if updated == range {
+        None
+    } else {
+        Some(updated)
+    }
+}
+
 pub fn extract_all_gh_pkgs_and_versions(
     releases: Vec<Release>,
-) -> HashMap<String, Vec<ReleaseInfo>> {
+) -> Result<HashMap<String, Vec<ReleaseInfo>>> {
     let mut all_release_info: HashMap<String, Vec<ReleaseInfo>> = HashMap::new();
 
     for release in releases {
-        // For monorepos with multiple packages
-        if let Some((app_name, version_str)) = release.tag_name.split_once('@') {
+        // For monorepos with multiple packages. The last `@` separates the version, so scoped
+        // names like `@scope/pkg@v1.0.0` keep their scope.
+        if let Some((app_name, version_str)) = release.tag_name.rsplit_once('@') {
             // Remove the 'v' prefix if present
             let has_started_with_v = version_str.starts_with('v');
             let version_str = version_str.strip_prefix('v').unwrap_or(version_str);
@@ -67,7 +338,10 @@ pub fn extract_all_gh_pkgs_and_versions(
                     all_release_info.insert(app_name.to_string(), vec![release_info]);
                 }
             } else {
-                panic!("Invalid version format for tag: {}", release.tag_name);
+                return Err(RemaError::BadTag(format!(
+                    "Invalid version format for tag: {}",
+                    release.tag_name
+                )));
             }
         } else {
             let app_name = ""; // TODO: get the app name from the repo name, etc.
@@ -96,12 +370,15 @@ pub fn extract_all_gh_pkgs_and_versions(
                     all_release_info.insert(app_name.to_string(), vec![release_info]);
                 }
             } else {
-                panic!("Invalid version format for tag: {}", release.tag_name);
+                return Err(RemaError::BadTag(format!(
+                    "Invalid version format for tag: {}",
+                    release.tag_name
+                )));
             }
         }
     }
 
-    all_release_info
+    Ok(all_release_info)
 }
 
 pub fn extract_latest_gh_pkgs_and_versions(
@@ -113,12 +390,12 @@ pub fn extract_latest_gh_pkgs_and_versions(
         for release in releases {
             // Check if the current version is newer
             if let Some(current_rel) = latest_versions.get(app_name) {
-                if &release.version > &current_rel.version {
+                if release.version > current_rel.version {
                     let release_info = ReleaseInfo {
                         version: release.version.clone(),
-                        has_v_prefix: release.has_v_prefix.clone(),
+                        has_v_prefix: release.has_v_prefix,
                         local_pkg_files: release.local_pkg_files.clone(),
-                        local_only: release.local_only.clone(),
+                        local_only: release.local_only,
                     };
                     latest_versions.insert(app_name.to_string(), release_info);
                 }
@@ -126,9 +403,9 @@ pub fn extract_latest_gh_pkgs_and_versions(
                 // Insert the first version encountered
                 let release_info = ReleaseInfo {
                     version: release.version.clone(),
-                    has_v_prefix: release.has_v_prefix.clone(),
+                    has_v_prefix: release.has_v_prefix,
                     local_pkg_files: release.local_pkg_files.clone(),
-                    local_only: release.local_only.clone(),
+                    local_only: release.local_only,
                 };
                 latest_versions.insert(app_name.to_string(), release_info);
             }
@@ -138,13 +415,510 @@ pub fn extract_latest_gh_pkgs_and_versions(
     latest_versions
 }
 
-pub fn create_release_title(ctx: &AppContext) -> String {
-    let release_info = ctx.get_target_release_info().unwrap_or_else(|| {
-        panic!("No target release info found. Run `bump` command first");
-    });
-    let app_name = ctx.get_selected_package().unwrap_or_else(|| {
-        panic!("No package selected");
-    });
+/// Short description of the changes since a release, e.g. `3 commits, 5 files changed`
+pub fn describe_changes(changes: &ChangeSummary) -> String {
+    if changes.commits == 0 {
+        return "no changes".to_string();
+    }
+
+    format!(
+        "{} commit{}, {} file{} changed",
+        changes.commits,
+        if changes.commits == 1 { "" } else { "s" },
+        changes.files_changed,
+        if changes.files_changed == 1 { "" } else { "s" }
+    )
+}
+
+/// Status of every package, sorted by name
+pub fn create_pkg_statuses(ctx: &AppContext) -> Vec<PkgStatus> {
+    let mut names: Vec<&String> = ctx.get_latest_versions().keys().collect();
+    names.sort();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let info = &ctx.get_latest_versions()[name];
+            let released = ctx
+                .get_all_versions()
+                .get(name)
+                .map(|versions| versions.as_slice())
+                .unwrap_or_default();
+
+            let latest_stable = released
+                .iter()
+                .filter(|rel| rel.version.pre.is_empty())
+                .max_by(|a, b| a.version.cmp(&b.version));
+            let latest_pre = released
+                .iter()
+                .filter(|rel| !rel.version.pre.is_empty())
+                .max_by(|a, b| a.version.cmp(&b.version));
+
+            let last_released_at = released
+                .iter()
+                .map(|rel| create_pkg_release_title(name, rel))
+                .filter_map(|tag| {
+                    ctx.get_releases()
+                        .iter()
+                        .find(|release| release.tag_name == tag)
+                })
+                .map(|release| release.published_at.clone())
+                .filter(|published_at| !published_at.is_empty())
+                .max();
+
+            let local_version = info
+                .local_pkg_files
+                .as_ref()
+                .and_then(|files| files.package_json.as_ref())
+                .and_then(|package_json| package_json.version.clone());
+            let changes = ctx.get_pkg_changes(name);
+
+            PkgStatus {
+                name: name.clone(),
+                latest_stable: latest_stable.map(|rel| rel.version.to_string()),
+                latest_pre: latest_pre.map(|rel| rel.version.to_string()),
+                local_version,
+                local_only: info.local_only,
+                last_released_at,
+                commits_behind: changes.map(|changes| changes.commits),
+                files_changed: changes.map(|changes| changes.files_changed),
+            }
+        })
+        .collect()
+}
+
+/// Parse a version bump given on the command line: `major`, `minor`, `patch`, `prerelease` to
+/// bump the existing pre-release, `local` to release an unreleased local version as is, or
+/// `prerelease:<alpha|beta|rc>[:<major|minor|patch|retain>]` for a new pre-release. Without a
+/// base, a new pre-release retains the version if it is a pre-release and bumps the patch
+/// otherwise.
+pub fn parse_version_bump(spec: &str, current: &Version) -> Result<VersionBump> {
+    let mut parts = spec.split(':');
+    let kind = parts.next().unwrap_or_default();
+    let pre_type = parts.next();
+    let base = parts.next();
+    if parts.next().is_some() {
+        return Err(RemaError::InvalidInput(format!(
+            "Invalid version bump: {}",
+            spec
+        )));
+    }
+
+    let bump = match (kind, pre_type, base) {
+        ("major", None, None) => VersionBump::Major,
+        ("minor", None, None) => VersionBump::Minor,
+        ("patch", None, None) => VersionBump::Patch,
+        ("local", None, None) => VersionBump::RetainIfUnreleased,
+        ("prerelease", None, None) => VersionBump::Pre,
+        ("prerelease", Some(pre_type), base) => {
+            let pre_type = match pre_type {
+                "alpha" => PreReleaseType::Alpha,
+                "beta" => PreReleaseType::Beta,
+                "rc" => PreReleaseType::Rc,
+                _ => {
+                    return Err(RemaError::InvalidInput(format!(
+                        "Invalid pre-release type: {}",
+                        pre_type
+                    )))
+                }
+            };
+            let base = match base {
+                Some("major") => PreReleaseVersionBump::Major,
+                Some("minor") => PreReleaseVersionBump::Minor,
+                Some("patch") => PreReleaseVersionBump::Patch,
+                Some("retain") => PreReleaseVersionBump::Retain,
+                None if !current.pre.is_empty() => PreReleaseVersionBump::Retain,
+                None => PreReleaseVersionBump::Patch,
+                Some(base) => {
+                    return Err(RemaError::InvalidInput(format!(
+                        "Invalid pre-release base: {}",
+                        base
+                    )))
+                }
+            };
+            VersionBump::PreNew(pre_type, base)
+        }
+        _ => {
+            return Err(RemaError::InvalidInput(format!(
+                "Invalid version bump: {}",
+                spec
+            )))
+        }
+    };
+
+    Ok(bump)
+}
+
+/// Compute the next version of a package the same way an interactive release would, rejecting
+/// bumps the interactive release doesn't offer for it
+pub fn compute_next_version(
+    ctx: &AppContext,
+    pkg_name: &str,
+    bump_spec: &str,
+) -> Result<NextVersion> {
+    let info = ctx
+        .get_latest_versions()
+        .get(pkg_name)
+        .ok_or_else(|| RemaError::InvalidInput(format!("Unknown package: {}", pkg_name)))?;
+    let current = get_current_version(ctx, pkg_name)?;
+
+    let bump = parse_version_bump(bump_spec, &current)?;
+    match bump {
+        VersionBump::Pre if current.pre.is_empty() => {
+            return Err(RemaError::InvalidInput(format!(
+                "{} {} is not a pre-release",
+                pkg_name, current
+            )));
+        }
+        VersionBump::RetainIfUnreleased if !info.local_only => {
+            return Err(RemaError::InvalidInput(format!(
+                "{} {} is already released",
+                pkg_name, current
+            )));
+        }
+        _ => {}
+    }
+
+    let release_info = bump_pkg_version(ctx, pkg_name, bump)?;
+
+    Ok(NextVersion {
+        package: pkg_name.to_string(),
+        current_version: current.to_string(),
+        version: release_info.version.to_string(),
+        tag: create_pkg_release_title(pkg_name, &release_info),
+    })
+}
+
+/// Report of the release targets, given the files changed per package and the release URLs in
+/// the order of the targets
+pub fn create_release_report(
+    ctx: &AppContext,
+    changed_files: &[(String, String)],
+    created_releases: &[CreatedRelease],
+    dry_run: bool,
+) -> ReleaseReport {
+    let releases = ctx
+        .get_release_targets()
+        .iter()
+        .enumerate()
+        .map(|(i, target)| ReleaseResult {
+            package: target.pkg_name.clone(),
+            previous_version: target.previous_version.to_string(),
+            new_version: target.release_info.version.to_string(),
+            tag: target.title.clone(),
+            release_url: created_releases
+                .get(i)
+                .map(|created| created.url.clone())
+                .filter(|url| !url.is_empty()),
+            latest: resolve_latest(ctx, target),
+            asset_urls: created_releases
+                .get(i)
+                .map(|created| created.asset_urls.clone())
+                .unwrap_or_default(),
+            files_changed: changed_files
+                .iter()
+                .filter(|(pkg_name, _)| *pkg_name == target.pkg_name)
+                .map(|(_, path)| path.clone())
+                .collect(),
+        })
+        .collect();
+
+    let mut files_changed: Vec<String> =
+        changed_files.iter().map(|(_, path)| path.clone()).collect();
+    files_changed.sort();
+    files_changed.dedup();
+
+    ReleaseReport {
+        dry_run,
+        draft: ctx.is_draft(),
+        releases,
+        files_changed,
+    }
+}
+
+/// Package name and version of a tag in the `name@vX.Y.Z`, `@scope/name@vX.Y.Z` or `vX.Y.Z`
+/// format, with an empty name for single package repositories
+pub fn parse_release_tag(tag: &str) -> Option<(&str, Version)> {
+    let (pkg_name, version_str) = tag.rsplit_once('@').unwrap_or(("", tag));
+    let version = Version::parse(version_str.strip_prefix('v').unwrap_or(version_str)).ok()?;
+
+    Some((pkg_name, version))
+}
+
+/// Compare the release tags of the repository with its releases, sorted by tag. Tags that
+/// aren't in the release format are ignored.
+pub fn create_audit_findings(
+    releases: &[Release],
+    local_tags: &[String],
+    remote_tags: &[String],
+) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+    let finding = |issue: AuditIssue, tag: &str, detail: &str| AuditFinding {
+        issue,
+        tag: tag.to_string(),
+        detail: detail.to_string(),
+    };
+
+    let mut tags: Vec<&String> = local_tags.iter().chain(remote_tags).collect();
+    tags.sort();
+    tags.dedup();
+    for tag in tags {
+        if parse_release_tag(tag).is_none()
+            || releases.iter().any(|release| release.tag_name == *tag)
+        {
+            continue;
+        }
+        let location = match (local_tags.contains(tag), remote_tags.contains(tag)) {
+            (true, true) => "Tagged locally and on origin",
+            (true, false) => "Only tagged locally",
+            _ => "Only tagged on origin",
+        };
+        findings.push(finding(AuditIssue::TagWithoutRelease, tag, location));
+    }
+
+    findings.extend(find_prerelease_mismatches(releases));
+
+    for release in releases {
+        let tag = release.tag_name.as_str();
+        // Drafts get their tag when published
+        if release.is_draft {
+            findings.push(finding(
+                AuditIssue::DraftRelease,
+                tag,
+                "Not published, so not counted as released",
+            ));
+            continue;
+        }
+        if !local_tags.iter().any(|local_tag| local_tag == tag) {
+            findings.push(finding(
+                AuditIssue::MissingLocalTag,
+                tag,
+                "Run `git fetch --tags` to get it",
+            ));
+        }
+        if !remote_tags.iter().any(|remote_tag| remote_tag == tag) {
+            findings.push(finding(
+                AuditIssue::MissingRemoteTag,
+                tag,
+                "The release points to a tag that doesn't exist on origin",
+            ));
+        }
+    }
+
+    findings.sort_by(|a, b| a.tag.cmp(&b.tag).then(a.issue.cmp(&b.issue)));
+    findings
+}
+
+/// Releases marked as pre-release without a pre-release version, or the other way around. The
+/// version of the tag is what rema goes by.
+pub fn find_prerelease_mismatches(releases: &[Release]) -> Vec<AuditFinding> {
+    releases
+        .iter()
+        .filter_map(|release| {
+            let (_, version) = parse_release_tag(&release.tag_name)?;
+            let detail = match (release.is_prerelease, version.pre.is_empty()) {
+                (true, true) => "Marked as pre-release, but the version is stable",
+                (false, false) => "Not marked as pre-release, but the version is a pre-release",
+                _ => return None,
+            };
+
+            Some(AuditFinding {
+                issue: AuditIssue::PrereleaseMismatch,
+                tag: release.tag_name.clone(),
+                detail: detail.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Compare the version of a release tag with the content of the package.json at the tag
+pub fn check_manifest_version(tag: &str, manifest: &str) -> Option<AuditFinding> {
+    let (_, tag_version) = parse_release_tag(tag)?;
+    let manifest_version = serde_json::from_str::<serde_json::Value>(manifest)
+        .ok()
+        .and_then(|json| json["version"].as_str().map(str::to_string));
+
+    let detail = match manifest_version {
+        Some(version) if Version::parse(&version).is_ok_and(|v| v == tag_version) => return None,
+        Some(version) => format!("package.json has version {}", version),
+        None => "package.json has no readable version".to_string(),
+    };
+
+    Some(AuditFinding {
+        issue: AuditIssue::ManifestMismatch,
+        tag: tag.to_string(),
+        detail,
+    })
+}
+
+pub fn create_release_title(ctx: &AppContext) -> Result<String> {
+    let release_info = ctx.get_target_release_info().ok_or_else(|| {
+        RemaError::InvalidInput(
+            "No target release info found. Run `bump` command first".to_string(),
+        )
+    })?;
+    let app_name = ctx
+        .get_selected_package()
+        .ok_or_else(|| RemaError::InvalidInput("No package selected".to_string()))?;
+
+    Ok(create_pkg_release_title(app_name, release_info))
+}
+
+/// Whether a release gets the Latest badge. A `--latest` flag for the package wins over one for
+/// every package, which wins over the config. Pre-releases are left to the forge, which never
+/// marks them as latest. None when nothing decides.
+pub fn resolve_latest(ctx: &AppContext, target: &ReleaseTarget) -> Option<bool> {
+    let overrides = ctx.get_latest_overrides();
+    let for_pkg = overrides
+        .iter()
+        .rfind(|o| o.package.as_deref() == Some(target.pkg_name.as_str()));
+    let for_all = overrides.iter().rfind(|o| o.package.is_none());
+    if let Some(latest_override) = for_pkg.or(for_all) {
+        return Some(latest_override.latest);
+    }
+
+    let version = parse_release_tag(&target.title)
+        .map(|(_, version)| version)
+        .unwrap_or(target.release_info.version.clone());
+    if !version.pre.is_empty() {
+        return None;
+    }
+
+    let config = &ctx.get_config().latest;
+    if !config.packages.is_empty() && !config.packages.contains(&target.pkg_name) {
+        return Some(false);
+    }
+
+    let highest_stable = ctx
+        .get_all_versions()
+        .get(&target.pkg_name)
+        .and_then(|infos| {
+            infos
+                .iter()
+                .map(|info| &info.version)
+                .filter(|v| v.pre.is_empty())
+                .max()
+        });
+    let is_backport = highest_stable.is_some_and(|highest| version < *highest);
+    if is_backport && !config.backports {
+        return Some(false);
+    }
+
+    // Being listed only makes a release eligible, a backport is still left to the forge
+    if config.packages.is_empty() || is_backport {
+        None
+    } else {
+        Some(true)
+    }
+}
+
+/// Asset globs of a release, from the config and the `--asset` flags
+pub fn resolve_asset_patterns(ctx: &AppContext, target: &ReleaseTarget) -> Vec<AssetPattern> {
+    let configured = ctx
+        .get_config()
+        .assets
+        .get(&target.pkg_name)
+        .cloned()
+        .unwrap_or_default();
+    let flags = ctx
+        .get_asset_globs()
+        .iter()
+        .filter(|flag| {
+            flag.package
+                .as_ref()
+                .is_none_or(|package| *package == target.pkg_name)
+        })
+        .map(|flag| AssetPattern {
+            glob: flag.glob.clone(),
+            optional: false,
+        });
+
+    configured.into_iter().chain(flags).collect()
+}
+
+/// Environment variables of a hook run for a release
+pub fn create_hook_env(hook: &str, target: &ReleaseTarget) -> Vec<(String, String)> {
+    [
+        ("REMA_HOOK", hook.to_string()),
+        ("REMA_PACKAGE", target.pkg_name.clone()),
+        ("REMA_PACKAGE_DIR", get_pkg_dir(target)),
+        ("REMA_PREVIOUS_VERSION", target.previous_version.to_string()),
+        ("REMA_VERSION", target.release_info.version.to_string()),
+        ("REMA_TAG", target.title.clone()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_string(), value))
+    .collect()
+}
+
+/// Environment variables of a hook run once for all releases, with the package names, versions
+/// and tags separated by spaces, in the same order
+pub fn create_tree_hook_env(hook: &str, targets: &[ReleaseTarget]) -> Vec<(String, String)> {
+    let join = |field: fn(&ReleaseTarget) -> String| {
+        targets.iter().map(field).collect::<Vec<String>>().join(" ")
+    };
+
+    vec![
+        ("REMA_HOOK".to_string(), hook.to_string()),
+        (
+            "REMA_PACKAGES".to_string(),
+            join(|target| target.pkg_name.clone()),
+        ),
+        (
+            "REMA_PREVIOUS_VERSIONS".to_string(),
+            join(|target| target.previous_version.to_string()),
+        ),
+        (
+            "REMA_VERSIONS".to_string(),
+            join(|target| target.release_info.version.to_string()),
+        ),
+        ("REMA_TAGS".to_string(), join(|target| target.title.clone())),
+    ]
+}
+
+/// Name of the checksums file uploaded with the assets of a release
+pub const CHECKSUMS_FILE_NAME: &str = "SHA256SUMS";
+
+/// Checksums in the format of `sha256sum`, which `sha256sum --check` verifies. Takes file names
+/// and their hex digests.
+pub fn create_checksums_file(checksums: &[(String, String)]) -> String {
+    checksums
+        .iter()
+        .map(|(name, digest)| format!("{}  {}\n", digest, name))
+        .collect()
+}
+
+/// File name of the source tarball of a release without extension, which is also the directory
+/// its files are in, e.g. `scope-core-v1.2.0` for `@scope/core@v1.2.0`
+pub fn create_source_archive_name(tag: &str) -> String {
+    tag.trim_start_matches('@').replace(['@', '/'], "-")
+}
+
+/// Markdown table of the checksums, to append to the release notes
+pub fn create_checksum_table(checksums: &[(String, String)]) -> String {
+    let rows: String = checksums
+        .iter()
+        .map(|(name, digest)| format!("| `{}` | `{}` |\n", name, digest))
+        .collect();
+
+    format!(
+        "## Checksums\n\n| File | SHA-256 |\n| --- | --- |\n{}",
+        rows
+    )
+}
+
+/// Directory of the package of a release, relative to where rema runs
+pub fn get_pkg_dir(target: &ReleaseTarget) -> String {
+    target
+        .release_info
+        .local_pkg_files
+        .as_ref()
+        .and_then(|files| files.dir.clone())
+        .unwrap_or(".".to_string())
+}
+
+/// Release title, which doubles as the tag, for the given package and release
+pub fn create_pkg_release_title(app_name: &str, release_info: &ReleaseInfo) -> String {
     let version = release_info.version.to_string();
 
     if app_name.is_empty() {
@@ -153,31 +927,86 @@ pub fn create_release_title(ctx: &AppContext) -> String {
         } else {
             version
         }
+    } else if release_info.has_v_prefix {
+        format!("{}@v{}", app_name, version)
     } else {
-        if release_info.has_v_prefix {
-            format!("{}@v{}", app_name, version)
-        } else {
-            format!("{}@{}", app_name, version)
-        }
+        format!("{}@{}", app_name, version)
     }
 }
 
-pub fn bump_version(ctx: &AppContext, bump: VersionBump) -> ReleaseInfo {
+pub fn bump_version(ctx: &AppContext, bump: VersionBump) -> Result<ReleaseInfo> {
+    let selected_pkg = ctx
+        .get_selected_package()
+        .ok_or_else(|| RemaError::InvalidInput("No package selected".to_string()))?;
+
+    bump_pkg_version(ctx, selected_pkg, bump)
+}
+
+/// The version a bump is based on. For a package in a version group, this is the highest
+/// current version in the group.
+pub fn get_current_version(ctx: &AppContext, pkg_name: &str) -> Result<Version> {
+    let latest_versions = ctx.get_latest_versions();
+    let version = latest_versions
+        .get(pkg_name)
+        .map(|info| info.version.clone())
+        .ok_or_else(|| RemaError::InvalidInput(format!("Unknown package: {}", pkg_name)))?;
+
+    let highest = ctx
+        .get_version_group_members(pkg_name)
+        .iter()
+        .filter_map(|member| latest_versions.get(member))
+        .map(|info| info.version.clone())
+        .fold(version, |highest, member_version| {
+            if member_version > highest {
+                member_version
+            } else {
+                highest
+            }
+        });
+
+    Ok(highest)
+}
+
+/// Release infos for the other members of the version group of a package, all at the version
+/// of the package's release
+pub fn create_version_group_release_infos(
+    ctx: &AppContext,
+    pkg_name: &str,
+    release_info: &ReleaseInfo,
+) -> Vec<(String, ReleaseInfo)> {
+    ctx.get_version_group_members(pkg_name)
+        .into_iter()
+        .map(|member| {
+            let member_info = &ctx.get_latest_versions()[&member];
+            let member_release_info = ReleaseInfo {
+                version: release_info.version.clone(),
+                has_v_prefix: member_info.has_v_prefix,
+                local_pkg_files: member_info.local_pkg_files.clone(),
+                local_only: member_info.local_only,
+            };
+            (member, member_release_info)
+        })
+        .collect()
+}
+
+/// Compute the next release of any known package, not only the selected one
+pub fn bump_pkg_version(
+    ctx: &AppContext,
+    selected_pkg: &str,
+    bump: VersionBump,
+) -> Result<ReleaseInfo> {
     let latest_versions = ctx.get_latest_versions();
-    let selected_pkg = ctx.get_selected_package().unwrap_or_else(|| {
-        panic!("No package selected");
-    });
 
-    let selected_pkg_release_info = latest_versions.get(selected_pkg).unwrap_or_else(|| {
-        panic!("Failed to get version for package: {}", selected_pkg);
-    });
+    let selected_pkg_release_info = latest_versions
+        .get(selected_pkg)
+        .ok_or_else(|| RemaError::InvalidInput(format!("Unknown package: {}", selected_pkg)))?;
 
-    let version = selected_pkg_release_info.version.clone();
-    let has_v_prefix = selected_pkg_release_info.has_v_prefix.clone();
+    let version = get_current_version(ctx, selected_pkg)?;
+    let has_v_prefix = selected_pkg_release_info.has_v_prefix;
     let local_pkg_files = selected_pkg_release_info.local_pkg_files.clone();
-    let local_only = selected_pkg_release_info.local_only.clone();
+    let local_only = selected_pkg_release_info.local_only;
 
-    match bump {
+    let release_info = match bump {
         VersionBump::RetainIfUnreleased => ReleaseInfo {
             version: Version {
                 major: version.major,
@@ -231,21 +1060,25 @@ pub fn bump_version(ctx: &AppContext, bump: VersionBump) -> ReleaseInfo {
                 major: version.major,
                 minor: version.minor,
                 patch: version.patch,
-                pre: increment_pre(&version.pre),
+                pre: increment_pre(&version.pre)?,
                 build: BuildMetadata::EMPTY,
             },
             has_v_prefix,
             local_pkg_files,
             local_only,
         },
-        VersionBump::PreNew(pre_type, base) => generate_pre_release(ctx, &version, base, pre_type),
-    }
+        VersionBump::PreNew(pre_type, base) => {
+            generate_pre_release(ctx, selected_pkg, &version, base, pre_type)?
+        }
+    };
+
+    Ok(release_info)
 }
 
 pub fn match_local_pkgs_with_gh_pkgs(
     gh_versions: &mut HashMap<String, ReleaseInfo>,
     local_pkg_files: &[LocalPackageFiles],
-) -> Result<HashMap<String, ReleaseInfo>, Box<dyn Error>> {
+) -> Result<HashMap<String, ReleaseInfo>> {
     // Create a clone of the HashMap to modify and return
     let mut updated_versions = gh_versions.clone();
 
@@ -254,12 +1087,10 @@ pub fn match_local_pkgs_with_gh_pkgs(
 
     // First, match GitHub versions with local packages
     for (gh_pkg_name, release_info) in updated_versions.iter_mut() {
-        if let Some(matching_pkg) = local_pkg_files.iter().find(|local_pkg| {
-            local_pkg
-                .name
-                .as_ref()
-                .map_or(false, |name| name == gh_pkg_name)
-        }) {
+        if let Some(matching_pkg) = local_pkg_files
+            .iter()
+            .find(|local_pkg| local_pkg.name.as_ref() == Some(gh_pkg_name))
+        {
             release_info.local_pkg_files = Some(matching_pkg.clone());
             matched_local_pkgs.push(matching_pkg.name.clone().unwrap_or_default());
         }
@@ -273,11 +1104,19 @@ pub fn match_local_pkgs_with_gh_pkgs(
                 continue;
             }
 
-            let existing_version = local_pkg
+            let existing_version = match local_pkg
                 .package_json
                 .as_ref()
                 .and_then(|pkg| pkg.version.as_ref())
-                .map_or(Version::new(0, 0, 0), |v| Version::parse(v).unwrap());
+            {
+                Some(v) => Version::parse(v).map_err(|e| {
+                    RemaError::Manifest(format!(
+                        "Invalid version {} in package.json of {}: {}",
+                        v, pkg_name, e
+                    ))
+                })?,
+                None => Version::new(0, 0, 0),
+            };
 
             // Create a new ReleaseInfo for unmatched local package
             let local_only_release = ReleaseInfo {
@@ -296,10 +1135,11 @@ pub fn match_local_pkgs_with_gh_pkgs(
 
 fn generate_pre_release(
     ctx: &AppContext,
+    pkg_name: &str,
     existing_version: &Version,
     base: PreReleaseVersionBump,
     pre_type: PreReleaseType,
-) -> ReleaseInfo {
+) -> Result<ReleaseInfo> {
     let (major, minor, patch) = match base {
         PreReleaseVersionBump::Major => (existing_version.major + 1, 0, 0),
         PreReleaseVersionBump::Minor => (existing_version.major, existing_version.minor + 1, 0),
@@ -321,17 +1161,13 @@ fn generate_pre_release(
         PreReleaseType::Rc => "rc.1",
     };
 
-    let pkg_name = match ctx.get_selected_package() {
-        Some(name) => name,
-        None => panic!("No package selected"),
-    };
     let latest_versions = ctx.get_latest_versions();
-    let selected_pkg_release_info = latest_versions.get(pkg_name).unwrap_or_else(|| {
-        panic!("Failed to get version for package: {}", pkg_name);
-    });
-    let has_v_prefix = selected_pkg_release_info.has_v_prefix.clone();
+    let selected_pkg_release_info = latest_versions
+        .get(pkg_name)
+        .ok_or_else(|| RemaError::InvalidInput(format!("Unknown package: {}", pkg_name)))?;
+    let has_v_prefix = selected_pkg_release_info.has_v_prefix;
     let local_pkg_files = selected_pkg_release_info.local_pkg_files.clone();
-    let local_only = selected_pkg_release_info.local_only.clone();
+    let local_only = selected_pkg_release_info.local_only;
 
     let new_release_info = ReleaseInfo {
         version: Version {
@@ -348,9 +1184,8 @@ fn generate_pre_release(
 
     // Check if the pre-release already exists.
     let existing_pre = ctx.find_existing_prerelease(pkg_name, &new_release_info.version, pre_type);
-    if existing_pre.is_some() {
-        let existing_pre = existing_pre.unwrap();
-        panic!(
+    if let Some(existing_pre) = existing_pre {
+        return Err(RemaError::BadTag(format!(
             "Failed to generate pre-release. {} already exists, or is of older version, for {} ({}.{}.{}-{})",
             new_release_info.version,
             pkg_name,
@@ -358,28 +1193,413 @@ fn generate_pre_release(
             existing_pre.version.minor,
             existing_pre.version.patch,
             existing_pre.version.pre
-        )
+        )));
     }
 
-    new_release_info
+    Ok(new_release_info)
 }
 
-fn increment_pre(pre: &Prerelease) -> Prerelease {
+fn increment_pre(pre: &Prerelease) -> Result<Prerelease> {
     if let Some((ident, num_str)) = pre.as_str().split_once('.') {
         if let Ok(num) = num_str.parse::<u64>() {
-            return Prerelease::new(&format!("{}.{}", ident, num + 1)).unwrap();
+            if let Ok(pre) = Prerelease::new(&format!("{}.{}", ident, num + 1)) {
+                return Ok(pre);
+            }
         }
     }
-    panic!(
+    Err(RemaError::BadTag(format!(
         "Invalid pre-release format: {}. Expected to delimit by '.'. Example 'beta.1'",
         pre
-    );
+    )))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn create_local_pkg(name: &str, dependencies: &[(&str, &str)]) -> LocalPackageFiles {
+        LocalPackageFiles {
+            name: Some(name.to_string()),
+            package_json: Some(crate::read::PackageJson {
+                name: Some(name.to_string()),
+                version: Some("1.0.0".to_string()),
+                path: None,
+                private: false,
+                dependencies: dependencies
+                    .iter()
+                    .map(|(dep, range)| (dep.to_string(), range.to_string()))
+                    .collect(),
+                dev_dependencies: HashMap::new(),
+                peer_dependencies: HashMap::new(),
+            }),
+            package_lock_json: None,
+            workspace_package_lock_json: None,
+            dir: None,
+        }
+    }
+
+    #[test]
+    fn should_find_direct_and_transitive_dependents() {
+        let pkgs = vec![
+            create_local_pkg("core", &[("axios", "^1.7.9")]),
+            create_local_pkg("react", &[("core", "^1.0.0")]),
+            create_local_pkg("ui", &[("react", "workspace:*"), ("core", "^1.0.0")]),
+            create_local_pkg("docs", &[("ui", "~1.0.0")]),
+        ];
+
+        let graph = DependencyGraph::new(&pkgs);
+
+        assert_eq!(graph.get_dependents("core"), vec!["react", "ui"]);
+        assert_eq!(graph.get_dependents("axios"), Vec::<String>::new());
+        assert_eq!(
+            graph.get_transitive_dependents("core"),
+            vec!["docs", "react", "ui"]
+        );
+    }
+
+    #[test]
+    fn should_update_dependency_range_and_keep_protocol() {
+        let version = Version::parse("1.2.0").unwrap();
+
+        assert_eq!(
+            update_dependency_range("^1.0.0", &version),
+            Some("^1.2.0".to_string())
+        );
+        assert_eq!(
+            update_dependency_range("~1.0.0", &version),
+            Some("~1.2.0".to_string())
+        );
+        assert_eq!(
+            update_dependency_range("1.0.0", &version),
+            Some("1.2.0".to_string())
+        );
+        assert_eq!(
+            update_dependency_range("workspace:^1.0.0", &version),
+            Some("workspace:^1.2.0".to_string())
+        );
+        assert_eq!(update_dependency_range("workspace:*", &version), None);
+        assert_eq!(update_dependency_range("workspace:^", &version), None);
+        assert_eq!(update_dependency_range("*", &version), None);
+        assert_eq!(update_dependency_range("^1.0.0 || ^2.0.0", &version), None);
+        assert_eq!(update_dependency_range("^1.2.0", &version), None);
+        assert_eq!(
+            update_dependency_range("^v1.0.0", &version),
+            Some("^v1.2.0".to_string())
+        );
+    }
+
+    #[test]
+    fn should_only_update_pre_release_ranges_to_pre_releases() {
+        let version = Version::parse("2.0.0-beta.1").unwrap();
+
+        assert_eq!(update_dependency_range("^1.0.0", &version), None);
+        assert_eq!(update_dependency_range("workspace:^1.0.0", &version), None);
+        assert_eq!(
+            update_dependency_range("^2.0.0-beta.0", &version),
+            Some("^2.0.0-beta.1".to_string())
+        );
+    }
+
+    #[test]
+    fn should_bump_version_group_from_highest_member_version() {
+        let releases = vec![
+            Release {
+                tag_name: "core@v1.2.0".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "react@v1.3.1".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "vue@1.1.0".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "docs@v0.1.0".to_string(),
+                ..Default::default()
+            },
+        ];
+        let mut ctx = AppContext::new(releases).unwrap();
+        ctx.set_config(crate::config::Config {
+            version_groups: vec![vec![
+                "core".to_string(),
+                "react".to_string(),
+                "vue".to_string(),
+            ]],
+            ..Default::default()
+        });
+
+        let core = bump_pkg_version(&ctx, "core", VersionBump::Minor).unwrap();
+        let mut members = create_version_group_release_infos(&ctx, "core", &core);
+        members.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(core.version, Version::new(1, 4, 0));
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].0, "react");
+        assert_eq!(members[0].1.version, Version::new(1, 4, 0));
+        assert_eq!(members[1].0, "vue");
+        assert_eq!(members[1].1.version, Version::new(1, 4, 0));
+        assert!(!members[1].1.has_v_prefix);
+        assert_eq!(
+            bump_pkg_version(&ctx, "docs", VersionBump::Patch)
+                .unwrap()
+                .version,
+            Version::new(0, 1, 1)
+        );
+    }
+
+    #[test]
+    fn should_create_pkg_statuses() {
+        let releases = vec![
+            Release {
+                tag_name: "core@v1.2.0".to_string(),
+                published_at: "2024-12-01T10:00:00Z".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "core@v1.3.0-rc.1".to_string(),
+                published_at: "2024-12-15T10:00:00Z".to_string(),
+                is_prerelease: true,
+                ..Default::default()
+            },
+            Release {
+                tag_name: "core@v1.1.0".to_string(),
+                published_at: "2024-11-01T10:00:00Z".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "docs@0.1.0".to_string(),
+                published_at: "2024-10-01T10:00:00Z".to_string(),
+                ..Default::default()
+            },
+        ];
+        let mut ctx = AppContext::new(releases).unwrap();
+        ctx.set_and_match_local_pkg_files(vec![
+            create_local_pkg("core", &[]),
+            create_local_pkg("ui", &[]),
+        ])
+        .unwrap();
+
+        let statuses = create_pkg_statuses(&ctx);
+
+        assert_eq!(
+            statuses.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["core", "docs", "ui"]
+        );
+        assert_eq!(statuses[0].latest_stable, Some("1.2.0".to_string()));
+        assert_eq!(statuses[0].latest_pre, Some("1.3.0-rc.1".to_string()));
+        assert_eq!(statuses[0].local_version, Some("1.0.0".to_string()));
+        assert_eq!(
+            statuses[0].last_released_at,
+            Some("2024-12-15T10:00:00Z".to_string())
+        );
+        assert!(!statuses[0].local_only);
+        assert_eq!(statuses[1].latest_pre, None);
+        assert_eq!(statuses[1].local_version, None);
+        assert!(statuses[2].local_only);
+        assert_eq!(statuses[2].latest_stable, None);
+        assert_eq!(statuses[2].last_released_at, None);
+        assert_eq!(statuses[2].commits_behind, None);
+    }
+
+    #[test]
+    fn should_keep_scope_of_scoped_package_tags() {
+        let ctx = AppContext::new(vec![
+            Release {
+                tag_name: "@acme/ui@v1.0.0".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "@acme/ui@v1.1.0".to_string(),
+                ..Default::default()
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(ctx.get_all_versions()["@acme/ui"].len(), 2);
+        assert_eq!(
+            ctx.get_latest_versions()["@acme/ui"].version,
+            Version::new(1, 1, 0)
+        );
+        assert_eq!(
+            parse_release_tag("@acme/ui@v1.0.0"),
+            Some(("@acme/ui", Version::new(1, 0, 0)))
+        );
+        assert_eq!(
+            parse_release_tag("core@1.2.0"),
+            Some(("core", Version::new(1, 2, 0)))
+        );
+        assert_eq!(
+            parse_release_tag("v2.0.0"),
+            Some(("", Version::new(2, 0, 0)))
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_release_tag() {
+        let result = AppContext::new(vec![Release {
+            tag_name: "tiger@latest".to_string(),
+            ..Default::default()
+        }]);
+
+        assert!(matches!(result, Err(RemaError::BadTag(_))));
+    }
+
+    #[test]
+    fn should_parse_version_bumps() {
+        let stable = Version::new(1, 2, 0);
+        let pre = Version::parse("1.3.0-alpha.2").unwrap();
+
+        assert!(matches!(
+            parse_version_bump("minor", &stable).unwrap(),
+            VersionBump::Minor
+        ));
+        assert!(matches!(
+            parse_version_bump("prerelease", &pre).unwrap(),
+            VersionBump::Pre
+        ));
+        assert!(matches!(
+            parse_version_bump("prerelease:beta", &stable).unwrap(),
+            VersionBump::PreNew(PreReleaseType::Beta, PreReleaseVersionBump::Patch)
+        ));
+        assert!(matches!(
+            parse_version_bump("prerelease:beta", &pre).unwrap(),
+            VersionBump::PreNew(PreReleaseType::Beta, PreReleaseVersionBump::Retain)
+        ));
+        assert!(matches!(
+            parse_version_bump("prerelease:rc:major", &stable).unwrap(),
+            VersionBump::PreNew(PreReleaseType::Rc, PreReleaseVersionBump::Major)
+        ));
+        assert!(parse_version_bump("prerelease:gamma", &stable).is_err());
+        assert!(parse_version_bump("minor:beta", &stable).is_err());
+        assert!(parse_version_bump("huge", &stable).is_err());
+    }
+
+    #[test]
+    fn should_compute_next_version_like_interactive_release() {
+        let releases = vec![
+            Release {
+                tag_name: "ui@v1.3.0-alpha.2".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "ui@v1.3.0-beta.1".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "core@1.2.0".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "@acme/ui@v1.0.0".to_string(),
+                ..Default::default()
+            },
+        ];
+        let ctx = AppContext::new(releases).unwrap();
+
+        let next = compute_next_version(&ctx, "ui", "prerelease").unwrap();
+        assert_eq!(next.current_version, "1.3.0-beta.1");
+        assert_eq!(next.version, "1.3.0-beta.2");
+        assert_eq!(next.tag, "ui@v1.3.0-beta.2");
+
+        let next = compute_next_version(&ctx, "core", "prerelease:beta").unwrap();
+        assert_eq!(next.tag, "core@1.2.1-beta.1");
+
+        let next = compute_next_version(&ctx, "@acme/ui", "prerelease:beta").unwrap();
+        assert_eq!(next.tag, "@acme/ui@v1.0.1-beta.1");
+
+        assert!(compute_next_version(&ctx, "core", "prerelease").is_err());
+        assert!(compute_next_version(&ctx, "core", "local").is_err());
+        assert!(compute_next_version(&ctx, "docs", "patch").is_err());
+    }
+
+    #[test]
+    fn should_not_compute_colliding_pre_release() {
+        let ctx = AppContext::new(vec![Release {
+            tag_name: "ui@v1.3.0-alpha.2".to_string(),
+            ..Default::default()
+        }])
+        .unwrap();
+
+        let result = compute_next_version(&ctx, "ui", "prerelease:alpha");
+        assert!(matches!(result, Err(RemaError::BadTag(_))));
+    }
+
+    #[test]
+    fn should_create_release_report() {
+        let mut ctx = AppContext::new(vec![Release {
+            tag_name: "core@v1.0.0".to_string(),
+            ..Default::default()
+        }])
+        .unwrap();
+        let release_info = bump_pkg_version(&ctx, "core", VersionBump::Patch).unwrap();
+        ctx.add_release_target(ReleaseTarget {
+            pkg_name: "core".to_string(),
+            previous_version: Version::new(1, 0, 0),
+            title: create_pkg_release_title("core", &release_info),
+            release_info,
+            description: String::new(),
+            publish_draft: false,
+        });
+        let changed_files = vec![
+            ("core".to_string(), "core/package.json".to_string()),
+            ("react".to_string(), "react/package.json".to_string()),
+            ("core".to_string(), "package-lock.json".to_string()),
+        ];
+
+        let dry_run = create_release_report(&ctx, &changed_files, &[], true);
+        let released = create_release_report(
+            &ctx,
+            &changed_files,
+            &[CreatedRelease {
+                url: "https://github.com/acme/repo/releases/tag/core@v1.0.1".to_string(),
+                asset_urls: Vec::new(),
+            }],
+            false,
+        );
+
+        assert_eq!(
+            dry_run.releases,
+            vec![ReleaseResult {
+                package: "core".to_string(),
+                previous_version: "1.0.0".to_string(),
+                new_version: "1.0.1".to_string(),
+                tag: "core@v1.0.1".to_string(),
+                release_url: None,
+                latest: None,
+                asset_urls: Vec::new(),
+                files_changed: vec![
+                    "core/package.json".to_string(),
+                    "package-lock.json".to_string()
+                ],
+            }]
+        );
+        assert_eq!(
+            dry_run.files_changed,
+            vec![
+                "core/package.json",
+                "package-lock.json",
+                "react/package.json"
+            ]
+        );
+        assert!(released.releases[0].release_url.is_some());
+    }
+
+    #[test]
+    fn should_describe_changes() {
+        let describe = |commits, files_changed| {
+            describe_changes(&ChangeSummary {
+                commits,
+                files_changed,
+            })
+        };
+
+        assert_eq!(describe(0, 0), "no changes");
+        assert_eq!(describe(1, 1), "1 commit, 1 file changed");
+        assert_eq!(describe(3, 5), "3 commits, 5 files changed");
+    }
+
     #[test]
     fn attach_v_prefix_to_title() {
         let r = vec![
@@ -406,15 +1626,15 @@ mod tests {
             local_only: false,
         };
 
-        let mut ctx = AppContext::new(r);
+        let mut ctx = AppContext::new(r).unwrap();
 
         ctx.set_selected_package("tiger".to_string());
         ctx.set_target_release_info(r_t);
-        assert_eq!(create_release_title(&ctx), "tiger@v1.0.1");
+        assert_eq!(create_release_title(&ctx).unwrap(), "tiger@v1.0.1");
 
         ctx.set_selected_package("elephant".to_string());
         ctx.set_target_release_info(r_e);
-        assert_eq!(create_release_title(&ctx), "elephant@1.0.1");
+        assert_eq!(create_release_title(&ctx).unwrap(), "elephant@1.0.1");
     }
 
     #[test]
@@ -434,7 +1654,7 @@ mod tests {
             },
         ];
 
-        let ctx = AppContext::new(test_releases);
+        let ctx = AppContext::new(test_releases).unwrap();
         let app_names = ctx.get_latest_pkg_names();
         assert_eq!(app_names.len(), 2);
         assert!(app_names.contains(&"tiger".to_string()));
@@ -457,16 +1677,16 @@ mod tests {
                 ..Default::default()
             },
         ];
-        let ctx = AppContext::new(r);
+        let ctx = AppContext::new(r).unwrap();
 
         let latest_releases = ctx.get_latest_versions();
         let tiger = latest_releases.get("tiger").unwrap();
         let elephant = latest_releases.get("elephant").unwrap();
         println!("{:?}", latest_releases);
 
-        assert_eq!(tiger.has_v_prefix, false);
+        assert!(!tiger.has_v_prefix);
         // TODO this should return true, debug!
-        assert_eq!(elephant.has_v_prefix, true);
+        assert!(elephant.has_v_prefix);
     }
 
     #[test]
@@ -486,7 +1706,7 @@ mod tests {
             },
         ];
 
-        let all_versions = extract_all_gh_pkgs_and_versions(releases);
+        let all_versions = extract_all_gh_pkgs_and_versions(releases).unwrap();
         assert_eq!(all_versions.len(), 2);
         assert_eq!(all_versions.get("tiger").unwrap().len(), 2);
         assert_eq!(all_versions.get("elephant").unwrap().len(), 1);
@@ -513,7 +1733,7 @@ mod tests {
             },
         ];
 
-        let ctx = AppContext::new(r);
+        let ctx = AppContext::new(r).unwrap();
         let latest_versions = ctx.get_latest_versions();
         assert_eq!(latest_versions.len(), 2);
         assert_eq!(
@@ -559,7 +1779,7 @@ mod tests {
             },
         ];
 
-        let ctx = AppContext::new(r);
+        let ctx = AppContext::new(r).unwrap();
         let latest_versions = ctx.get_latest_versions();
         assert_eq!(latest_versions.len(), 2);
         assert_eq!(
@@ -577,7 +1797,7 @@ mod tests {
     }
 
     #[test]
-    fn should_panic_when_generating_exact_same_existing_pre_release() {
+    fn should_fail_when_generating_exact_same_existing_pre_release() {
         let test_releases = vec![
             Release {
                 tag_name: "tiger@v1.0.0".to_string(),
@@ -605,7 +1825,7 @@ mod tests {
             },
         ];
 
-        let mut ctx = AppContext::new(test_releases);
+        let mut ctx = AppContext::new(test_releases).unwrap();
         ctx.set_selected_package("elephant".to_string());
 
         let version = Version::new(1, 0, 0);
@@ -615,13 +1835,12 @@ mod tests {
         let mut ctrl_version = Version::new(1, 0, 2);
         ctrl_version.pre = Prerelease::new("rc.1").unwrap();
 
-        let result =
-            std::panic::catch_unwind(|| generate_pre_release(&ctx, &version, base, pre_type));
-        assert!(result.is_err());
+        let result = generate_pre_release(&ctx, "elephant", &version, base, pre_type);
+        assert!(matches!(result, Err(RemaError::BadTag(_))));
     }
 
     #[test]
-    fn should_panic_when_same_pre_release_type_if_newer_exist() {
+    fn should_fail_when_same_pre_release_type_if_newer_exist() {
         // Should not be able to create a new pre-release of the
         // same type if it already exists on version
         // e.g.
@@ -640,7 +1859,7 @@ mod tests {
             },
         ];
 
-        let mut ctx = AppContext::new(test_releases);
+        let mut ctx = AppContext::new(test_releases).unwrap();
         ctx.set_selected_package("elephant".to_string());
 
         // Selected version (CLI)
@@ -651,9 +1870,269 @@ mod tests {
         let mut ctrl_version = Version::new(1, 0, 2);
         ctrl_version.pre = Prerelease::new("rc.1").unwrap();
 
-        let result = std::panic::catch_unwind(|| {
-            generate_pre_release(&ctx, &existing_version, base, pre_type)
-        });
-        assert!(result.is_err());
+        let result = generate_pre_release(&ctx, "elephant", &existing_version, base, pre_type);
+        assert!(matches!(result, Err(RemaError::BadTag(_))));
+    }
+
+    #[test]
+    fn should_find_drift_between_tags_and_releases() {
+        let release = |tag: &str, is_draft: bool| Release {
+            tag_name: tag.to_string(),
+            is_draft,
+            ..Default::default()
+        };
+        let releases = vec![
+            release("core@v1.0.0", false),
+            release("core@v1.1.0", false),
+            release("core@v2.0.0", true),
+        ];
+        let tags = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>();
+        let local_tags = tags(&["core@v1.0.0", "core@v0.9.0", "deploy-2024"]);
+        let remote_tags = tags(&["core@v1.0.0", "core@v1.1.0", "core@v0.9.0"]);
+
+        let findings = create_audit_findings(&releases, &local_tags, &remote_tags);
+
+        let issues: Vec<(&str, AuditIssue)> = findings
+            .iter()
+            .map(|finding| (finding.tag.as_str(), finding.issue))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                ("core@v0.9.0", AuditIssue::TagWithoutRelease),
+                ("core@v1.1.0", AuditIssue::MissingLocalTag),
+                ("core@v2.0.0", AuditIssue::DraftRelease),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_check_manifest_version_at_tag() {
+        assert_eq!(
+            check_manifest_version("core@v1.0.0", r#"{ "version": "1.0.0" }"#),
+            None
+        );
+        assert_eq!(
+            check_manifest_version("v1.0.0", r#"{ "version": "0.9.0" }"#).map(|f| f.detail),
+            Some("package.json has version 0.9.0".to_string())
+        );
+        assert!(check_manifest_version("core@v1.0.0", "{}").is_some());
+    }
+
+    #[test]
+    fn should_exclude_drafts_from_latest_versions_unless_configured() {
+        let releases = vec![
+            Release {
+                tag_name: "core@v1.0.0".to_string(),
+                ..Default::default()
+            },
+            Release {
+                tag_name: "core@v1.1.0".to_string(),
+                is_draft: true,
+                ..Default::default()
+            },
+        ];
+
+        let ctx = AppContext::new(releases.clone()).unwrap();
+        let with_drafts = AppContext::with_config(
+            releases,
+            crate::config::Config {
+                include_drafts: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            ctx.get_latest_versions()["core"].version,
+            Version::new(1, 0, 0)
+        );
+        assert_eq!(
+            with_drafts.get_latest_versions()["core"].version,
+            Version::new(1, 1, 0)
+        );
+    }
+
+    #[test]
+    fn should_find_releases_with_mismatching_prerelease_flag() {
+        let release = |tag: &str, is_prerelease: bool| Release {
+            tag_name: tag.to_string(),
+            is_prerelease,
+            ..Default::default()
+        };
+        let releases = vec![
+            release("core@v1.0.0", false),
+            release("core@v1.1.0-beta.0", true),
+            release("core@v1.1.0", true),
+            release("core@v1.2.0-rc.0", false),
+        ];
+
+        let tags: Vec<String> = find_prerelease_mismatches(&releases)
+            .into_iter()
+            .map(|finding| finding.tag)
+            .collect();
+
+        assert_eq!(tags, vec!["core@v1.1.0", "core@v1.2.0-rc.0"]);
+    }
+
+    #[test]
+    fn should_parse_latest_overrides() {
+        assert_eq!(
+            "false".parse::<LatestOverride>().unwrap(),
+            LatestOverride {
+                package: None,
+                latest: false
+            }
+        );
+        assert_eq!(
+            "@acme/core=true".parse::<LatestOverride>().unwrap(),
+            LatestOverride {
+                package: Some("@acme/core".to_string()),
+                latest: true
+            }
+        );
+        assert!("core=yes".parse::<LatestOverride>().is_err());
+    }
+
+    #[test]
+    fn should_list_every_release_in_tree_hook_env() {
+        let target = |pkg_name: &str, previous: &str, version: &str| {
+            let release_info = ReleaseInfo {
+                version: Version::parse(version).unwrap(),
+                has_v_prefix: true,
+                local_pkg_files: None,
+                local_only: false,
+            };
+            ReleaseTarget {
+                pkg_name: pkg_name.to_string(),
+                previous_version: Version::parse(previous).unwrap(),
+                title: create_pkg_release_title(pkg_name, &release_info),
+                release_info,
+                description: String::new(),
+                publish_draft: false,
+            }
+        };
+
+        let env = create_tree_hook_env(
+            "post_write",
+            &[
+                target("core", "1.0.0", "1.1.0"),
+                target("ui", "2.0.0", "2.0.1"),
+            ],
+        );
+
+        assert_eq!(
+            env,
+            [
+                ("REMA_HOOK", "post_write"),
+                ("REMA_PACKAGES", "core ui"),
+                ("REMA_PREVIOUS_VERSIONS", "1.0.0 2.0.0"),
+                ("REMA_VERSIONS", "1.1.0 2.0.1"),
+                ("REMA_TAGS", "core@v1.1.0 ui@v2.0.1"),
+            ]
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+        );
+    }
+
+    #[test]
+    fn should_resolve_latest_from_config_and_overrides() {
+        let releases = ["core@v2.0.0", "core@v1.0.0", "react@v1.0.0"]
+            .into_iter()
+            .map(|tag| Release {
+                tag_name: tag.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let mut ctx = AppContext::with_config(
+            releases,
+            crate::config::Config {
+                latest: crate::config::LatestConfig {
+                    packages: vec!["core".to_string()],
+                    backports: false,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let target = |pkg_name: &str, version: &str| {
+            let release_info = ReleaseInfo {
+                version: Version::parse(version).unwrap(),
+                has_v_prefix: true,
+                local_pkg_files: None,
+                local_only: false,
+            };
+            ReleaseTarget {
+                pkg_name: pkg_name.to_string(),
+                previous_version: Version::new(1, 0, 0),
+                title: create_pkg_release_title(pkg_name, &release_info),
+                release_info,
+                description: String::new(),
+                publish_draft: false,
+            }
+        };
+
+        assert_eq!(resolve_latest(&ctx, &target("core", "2.1.0")), Some(true));
+        assert_eq!(resolve_latest(&ctx, &target("core", "1.0.1")), Some(false));
+        assert_eq!(resolve_latest(&ctx, &target("core", "2.1.0-rc.0")), None);
+        assert_eq!(resolve_latest(&ctx, &target("react", "1.1.0")), Some(false));
+
+        let mut backports_ctx = AppContext::with_config(
+            ctx.get_releases().to_vec(),
+            crate::config::Config {
+                latest: crate::config::LatestConfig {
+                    packages: vec!["core".to_string()],
+                    backports: true,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            resolve_latest(&backports_ctx, &target("core", "1.0.1")),
+            None
+        );
+        assert_eq!(
+            resolve_latest(&backports_ctx, &target("core", "2.0.1")),
+            Some(true)
+        );
+        backports_ctx.set_latest_overrides(vec!["core=true".parse().unwrap()]);
+        assert_eq!(
+            resolve_latest(&backports_ctx, &target("core", "1.0.1")),
+            Some(true)
+        );
+
+        ctx.set_latest_overrides(vec![
+            "false".parse().unwrap(),
+            "react=true".parse().unwrap(),
+        ]);
+        assert_eq!(resolve_latest(&ctx, &target("core", "2.1.0")), Some(false));
+        assert_eq!(resolve_latest(&ctx, &target("react", "1.1.0")), Some(true));
+    }
+
+    #[test]
+    fn should_name_source_archives_after_tags() {
+        assert_eq!(
+            create_source_archive_name("@scope/core@v1.2.0"),
+            "scope-core-v1.2.0"
+        );
+        assert_eq!(create_source_archive_name("core@v1.2.0"), "core-v1.2.0");
+        assert_eq!(create_source_archive_name("v1.2.0"), "v1.2.0");
+    }
+
+    #[test]
+    fn should_format_checksums() {
+        let checksums = vec![
+            ("core-1.0.0.tgz".to_string(), "ab12".to_string()),
+            ("core-linux".to_string(), "cd34".to_string()),
+        ];
+
+        assert_eq!(
+            create_checksums_file(&checksums),
+            "ab12  core-1.0.0.tgz\ncd34  core-linux\n"
+        );
+        assert_eq!(
+            create_checksum_table(&checksums),
+            "## Checksums\n\n| File | SHA-256 |\n| --- | --- |\n| `core-1.0.0.tgz` | `ab12` |\n| `core-linux` | `cd34` |\n"
+        );
     }
 }
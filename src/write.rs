@@ -1,11 +1,20 @@
 use std::{
-    error::Error,
+    collections::HashMap,
+    env,
     fs::{self},
+    path::{Path, PathBuf},
 };
 
-use serde_json::Value;
+use semver::Version;
+use serde::Serialize;
+use serde_json::{Map, Value};
 
-use crate::ctx::AppContext;
+use crate::{
+    ctx::AppContext,
+    error::{RemaError, Result},
+    read::{LocalPackageFiles, LockfileMismatch},
+    transform::{self, ReleaseInfo, DEPENDENCY_FIELDS},
+};
 
 pub struct OriginalFile {
     pub contents: String,
@@ -14,101 +23,695 @@ pub struct OriginalFile {
 
 pub enum WriteTargetResult {
     NoWrites,
-    WritesCompleted {
-        original_pkg_json: Option<OriginalFile>,
-        original_pkg_json_lock: Option<OriginalFile>,
+    WritesCompleted { original_files: Vec<OriginalFile> },
+}
+
+/// Where in a json file a package is recorded
+#[derive(Debug, Clone, PartialEq)]
+enum PkgEntry {
+    PackageJson,
+    /// The package's own lockfile, or its entry in the root lockfile of a workspace
+    Lockfile {
+        workspace_key: Option<String>,
     },
 }
 
+/// Changes to apply to a package entry
+#[derive(Debug, Clone, Default)]
+struct PkgEntryUpdate {
+    version: Option<String>,
+    /// Released local dependencies mapped to their new version
+    dependency_versions: HashMap<String, Version>,
+}
+
 /// Write the target releases to local package.json and package-lock.json files. If successful,
 /// will return the original contents to restore in case error downstream.
+///
+/// Every package released in this run gets its new version, and local packages that depend on
+/// one of them get their dependency ranges updated. Lockfiles are updated wherever the package
+/// is recorded: the top-level `version`, `packages[""]` for lockfile v2/v3 and
+/// `packages["<workspace dir>"]` in the root lockfile of an npm workspace.
+///
+/// A lockfile that the package manager regenerates afterwards isn't edited, only backed up.
 pub fn write_target_release_to_local_files(
     ctx: &AppContext,
-) -> Result<WriteTargetResult, Box<dyn Error>> {
-    let release_info = ctx.get_target_release_info().unwrap_or_else(|| {
-        panic!("No target release info found. Run `bump` command first");
-    });
-    let local_pkg_files = match &release_info.local_pkg_files {
-        Some(files) => files,
-        None => return Ok(WriteTargetResult::NoWrites),
-    };
+    regenerated_lockfile: Option<&Path>,
+) -> Result<WriteTargetResult> {
+    let file_updates: Vec<(String, Vec<(PkgEntry, PkgEntryUpdate)>)> = plan_file_updates(ctx)
+        .into_iter()
+        .filter(|(path, _)| Some(Path::new(path)) != regenerated_lockfile)
+        .collect();
+
+    let mut original_files: Vec<OriginalFile> = Vec::new();
+    if let Some(lockfile) = regenerated_lockfile {
+        let contents = fs::read_to_string(lockfile)
+            .map_err(|e| RemaError::Manifest(format!("{}: {}", lockfile.display(), e)))?;
+        original_files.push(OriginalFile {
+            contents,
+            path: lockfile.to_string_lossy().to_string(),
+        });
+    }
+    if file_updates.is_empty() && original_files.is_empty() {
+        return Ok(WriteTargetResult::NoWrites);
+    }
+
+    for (path, updates) in file_updates {
+        let original = write_json(&path, &original_files, |json| {
+            for (entry, update) in &updates {
+                apply_pkg_entry_update(json, entry, update);
+            }
+        })
+        .inspect_err(|_| {
+            eprintln!(
+                "Error during writing to {}. Attempted to restore original file(s)...",
+                path
+            )
+        })?;
+        original_files.push(original);
+    }
 
-    let mut original_pkg_json_contents = String::new();
-    let mut original_pkg_json_lock_contents = String::new();
+    Ok(WriteTargetResult::WritesCompleted { original_files })
+}
 
-    let mut original_pkg_json_path = "";
-    let mut original_pkg_json_lock_path = "";
+/// The files that writing the release targets would change, paired with the package whose
+/// entry changes in them, without writing anything
+pub fn list_files_to_update(ctx: &AppContext) -> Vec<(String, String)> {
+    plan_pkg_entry_updates(ctx)
+        .into_iter()
+        .map(|planned| (planned.pkg_name, planned.path))
+        .collect()
+}
 
-    if let Some(path) = local_pkg_files
-        .package_json
-        .as_ref()
-        .and_then(|pkg| pkg.path.as_ref())
-    {
-        let path_str = path.as_str();
-        original_pkg_json_path = path_str;
-        let original_contents = fs::read_to_string(path_str)?;
+/// Write a file generated for the release of a tag, such as its checksums, to a temporary
+/// directory of its own and return its path
+pub fn write_release_file(tag: &str, name: &str, contents: &str) -> Result<PathBuf> {
+    let path = create_release_files_dir(tag)?.join(name);
 
-        let mut json: Value = serde_json::from_str(&original_contents)?;
+    fs::write(&path, contents)
+        .map_err(|e| RemaError::Manifest(format!("Failed to write {}: {}", path.display(), e)))?;
 
-        json["version"] = Value::String(release_info.version.clone().to_string());
+    Ok(path)
+}
 
-        let updated_contents = serde_json::to_string_pretty(&json)?;
+/// Temporary directory for the files generated for the release of a tag
+pub fn create_release_files_dir(tag: &str) -> Result<PathBuf> {
+    let dir = env::temp_dir().join(format!(
+        "rema-{}-{}",
+        tag.replace(['@', '/'], "-"),
+        std::process::id()
+    ));
 
-        if let Err(write_error) = fs::write(path_str, &updated_contents) {
-            // Restore the original contents if writing fails
-            fs::write(path_str, original_contents)?;
-            println!("Error during writing to package.json. Attempted to restore original file...");
-            return Err(Box::new(write_error));
-        }
+    fs::create_dir_all(&dir)
+        .map_err(|e| RemaError::Manifest(format!("Failed to create {}: {}", dir.display(), e)))?;
 
-        original_pkg_json_contents = original_contents;
+    Ok(dir)
+}
+
+/// Group the changes for every released package and its dependents by the file they're in, so
+/// each file is only written once
+fn plan_file_updates(ctx: &AppContext) -> Vec<(String, Vec<(PkgEntry, PkgEntryUpdate)>)> {
+    let mut file_updates: Vec<(String, Vec<(PkgEntry, PkgEntryUpdate)>)> = Vec::new();
+
+    for planned in plan_pkg_entry_updates(ctx) {
+        let entry_update = (planned.entry, planned.update);
+        match file_updates.iter_mut().find(|(p, _)| *p == planned.path) {
+            Some((_, updates)) => updates.push(entry_update),
+            None => file_updates.push((planned.path, vec![entry_update])),
+        }
     }
 
-    if let Some(path) = local_pkg_files
-        .package_lock_json
-        .as_ref()
-        .and_then(|pkg| pkg.path.as_ref())
-    {
-        let path_str = path.as_str();
-        original_pkg_json_lock_path = path_str;
-        let original_contents = fs::read_to_string(path_str)?;
+    file_updates
+}
 
-        let mut json: Value = serde_json::from_str(&original_contents)?;
+/// An update to one package entry in one file
+struct PlannedPkgEntryUpdate {
+    pkg_name: String,
+    path: String,
+    entry: PkgEntry,
+    update: PkgEntryUpdate,
+}
 
-        json["version"] = Value::String(release_info.version.clone().to_string());
+/// The entries to update for every resynced lockfile, every released package and every local
+/// package depending on one. Resyncs come first, so a release of the same package sets the
+/// version last.
+fn plan_pkg_entry_updates(ctx: &AppContext) -> Vec<PlannedPkgEntryUpdate> {
+    let mut planned_updates: Vec<PlannedPkgEntryUpdate> = ctx
+        .get_lockfile_resyncs()
+        .iter()
+        .filter_map(plan_lockfile_resync)
+        .collect();
 
-        let updated_contents = serde_json::to_string_pretty(&json)?;
+    let releases: Vec<(&str, &ReleaseInfo)> = ctx
+        .get_release_targets()
+        .iter()
+        .map(|target| (target.pkg_name.as_str(), &target.release_info))
+        .collect();
 
-        if let Err(write_error) = fs::write(path_str, &updated_contents) {
-            // Restore the original contents if writing fails
-            fs::write(path_str, original_contents)?;
-            if !original_pkg_json_contents.is_empty() {
-                fs::write(original_pkg_json_path, original_pkg_json_contents)?;
+    // The released packages carry their own files, which may not be in the scanned ones in tests
+    let mut local_pkgs: Vec<&LocalPackageFiles> = ctx
+        .get_local_pkg_files()
+        .iter()
+        .chain(ctx.get_private_pkg_files())
+        .collect();
+    for (_, info) in &releases {
+        if let Some(files) = &info.local_pkg_files {
+            if !local_pkgs.iter().any(|pkg| pkg.name == files.name) {
+                local_pkgs.push(files);
             }
+        }
+    }
 
-            println!("Error during writing to package-lock.json. Attempted to restore original file(s)...");
-            return Err(Box::new(write_error));
+    for pkg in local_pkgs {
+        let Some(name) = pkg.name.as_deref() else {
+            continue;
+        };
+
+        let mut update = PkgEntryUpdate {
+            version: releases
+                .iter()
+                .find(|(released, _)| *released == name)
+                .map(|(_, info)| info.version.to_string()),
+            ..Default::default()
+        };
+
+        if let Some(package_json) = &pkg.package_json {
+            for (released, info) in &releases {
+                let is_dependency = package_json.dependencies.contains_key(*released)
+                    || package_json.dev_dependencies.contains_key(*released)
+                    || package_json.peer_dependencies.contains_key(*released);
+                if is_dependency && *released != name {
+                    update
+                        .dependency_versions
+                        .insert(released.to_string(), info.version.clone());
+                }
+            }
         }
 
-        original_pkg_json_lock_contents = original_contents;
+        if update.version.is_none() && update.dependency_versions.is_empty() {
+            continue;
+        }
+
+        let entries = [
+            pkg.package_json
+                .as_ref()
+                .and_then(|pj| pj.path.clone())
+                .map(|path| (path, PkgEntry::PackageJson)),
+            pkg.package_lock_json
+                .as_ref()
+                .and_then(|lock| lock.path.clone())
+                .map(|path| {
+                    (
+                        path,
+                        PkgEntry::Lockfile {
+                            workspace_key: None,
+                        },
+                    )
+                }),
+            pkg.workspace_package_lock_json.as_ref().and_then(|lock| {
+                lock.path.clone().map(|path| {
+                    (
+                        path,
+                        PkgEntry::Lockfile {
+                            workspace_key: lock.workspace_key.clone(),
+                        },
+                    )
+                })
+            }),
+        ];
+
+        for (path, entry) in entries.into_iter().flatten() {
+            planned_updates.push(PlannedPkgEntryUpdate {
+                pkg_name: name.to_string(),
+                path,
+                entry,
+                update: update.clone(),
+            });
+        }
     }
 
-    Ok(WriteTargetResult::WritesCompleted {
-        original_pkg_json: if original_pkg_json_contents.is_empty() {
-            None
-        } else {
-            Some(OriginalFile {
-                contents: original_pkg_json_contents,
-                path: original_pkg_json_path.to_string(),
-            })
+    planned_updates
+}
+
+/// Set a mismatching lockfile to the package.json version
+fn plan_lockfile_resync(mismatch: &LockfileMismatch) -> Option<PlannedPkgEntryUpdate> {
+    Some(PlannedPkgEntryUpdate {
+        pkg_name: mismatch.pkg_name.clone()?,
+        path: mismatch.package_lock_json.path.clone()?,
+        entry: PkgEntry::Lockfile {
+            workspace_key: mismatch.package_lock_json.workspace_key.clone(),
         },
-        original_pkg_json_lock: if original_pkg_json_lock_contents.is_empty() {
-            None
-        } else {
-            Some(OriginalFile {
-                contents: original_pkg_json_lock_contents,
-                path: original_pkg_json_lock_path.to_string(),
-            })
+        update: PkgEntryUpdate {
+            version: Some(mismatch.package_json.version.clone()?),
+            ..Default::default()
         },
     })
 }
+
+/// Apply an update to the place a package is recorded in a parsed json file
+fn apply_pkg_entry_update(json: &mut Value, entry: &PkgEntry, update: &PkgEntryUpdate) {
+    let target = match entry {
+        PkgEntry::PackageJson => json.as_object_mut(),
+        PkgEntry::Lockfile {
+            workspace_key: Some(key),
+        } => json
+            .get_mut("packages")
+            .and_then(|pkgs| pkgs.get_mut(key))
+            .and_then(|entry| entry.as_object_mut()),
+        PkgEntry::Lockfile {
+            workspace_key: None,
+        } => {
+            if let Some(version) = &update.version {
+                json["version"] = Value::String(version.clone());
+            }
+            json.get_mut("packages")
+                .and_then(|pkgs| pkgs.get_mut(""))
+                .and_then(|entry| entry.as_object_mut())
+        }
+    };
+
+    if let Some(target) = target {
+        update_pkg_fields(target, update);
+    }
+}
+
+fn update_pkg_fields(target: &mut Map<String, Value>, update: &PkgEntryUpdate) {
+    if let Some(version) = &update.version {
+        target.insert("version".to_string(), Value::String(version.clone()));
+    }
+
+    for field in DEPENDENCY_FIELDS {
+        let Some(deps) = target.get_mut(field).and_then(|deps| deps.as_object_mut()) else {
+            continue;
+        };
+        for (dep, version) in &update.dependency_versions {
+            let updated = deps
+                .get(dep)
+                .and_then(|range| range.as_str())
+                .and_then(|range| transform::update_dependency_range(range, version));
+            if let Some(updated) = updated {
+                deps.insert(dep.clone(), Value::String(updated));
+            }
+        }
+    }
+}
+
+/// Update a json file in place and return its original contents. Key order, indentation and the
+/// trailing newline of the file are kept. If the write fails, the file and every previously
+/// written file are restored.
+fn write_json(
+    path: &str,
+    written: &[OriginalFile],
+    update: impl FnOnce(&mut Value),
+) -> Result<OriginalFile> {
+    let manifest_error =
+        |e: &dyn std::fmt::Display| RemaError::Manifest(format!("{}: {}", path, e));
+
+    let original_contents = fs::read_to_string(path).map_err(|e| manifest_error(&e))?;
+
+    let mut json: Value =
+        serde_json::from_str(&original_contents).map_err(|e| manifest_error(&e))?;
+    update(&mut json);
+
+    let updated_contents =
+        to_json_like(&json, &original_contents).map_err(|e| manifest_error(&e))?;
+
+    if let Err(write_error) = fs::write(path, &updated_contents) {
+        // Restore the original contents if writing fails
+        fs::write(path, &original_contents).map_err(|e| manifest_error(&e))?;
+        for original in written {
+            fs::write(&original.path, &original.contents).map_err(|e| manifest_error(&e))?;
+        }
+        return Err(manifest_error(&write_error));
+    }
+
+    Ok(OriginalFile {
+        contents: original_contents,
+        path: path.to_string(),
+    })
+}
+
+/// Pretty print json with the indentation and trailing newline of `original`
+fn to_json_like(json: &Value, original: &str) -> serde_json::Result<String> {
+    let indent = original
+        .lines()
+        .nth(1)
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .filter(|indent| !indent.is_empty())
+        .unwrap_or("  ");
+
+    let mut contents = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut contents, formatter);
+    json.serialize(&mut serializer)?;
+    if original.ends_with('\n') {
+        contents.push(b'\n');
+    }
+
+    Ok(String::from_utf8_lossy(&contents).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        forge::Release,
+        read::{PackageJson, PackageLockJson},
+        transform::{ReleaseTarget, VersionBump},
+    };
+
+    use super::*;
+
+    fn create_pkg_on_disk(
+        dir: &std::path::Path,
+        name: &str,
+        dependencies: &[(&str, &str)],
+    ) -> LocalPackageFiles {
+        let pkg_dir = dir.join(name);
+        fs::create_dir_all(&pkg_dir).unwrap();
+        let dependencies: HashMap<String, String> = dependencies
+            .iter()
+            .map(|(dep, range)| (dep.to_string(), range.to_string()))
+            .collect();
+        let path = pkg_dir.join("package.json");
+        fs::write(
+            &path,
+            json!({ "name": name, "version": "1.0.0", "dependencies": dependencies }).to_string(),
+        )
+        .unwrap();
+
+        LocalPackageFiles {
+            name: Some(name.to_string()),
+            package_json: Some(PackageJson {
+                name: Some(name.to_string()),
+                version: Some("1.0.0".to_string()),
+                path: Some(path.to_string_lossy().to_string()),
+                private: false,
+                dependencies,
+                dev_dependencies: HashMap::new(),
+                peer_dependencies: HashMap::new(),
+            }),
+            package_lock_json: None,
+            workspace_package_lock_json: None,
+            dir: Some(name.to_string()),
+        }
+    }
+
+    fn add_target(ctx: &mut AppContext, pkg_name: &str, bump: VersionBump) {
+        let release_info = transform::bump_pkg_version(ctx, pkg_name, bump).unwrap();
+        ctx.add_release_target(ReleaseTarget {
+            pkg_name: pkg_name.to_string(),
+            previous_version: Version::new(1, 0, 0),
+            title: transform::create_pkg_release_title(pkg_name, &release_info),
+            release_info,
+            description: String::new(),
+            publish_draft: false,
+        });
+    }
+
+    #[test]
+    fn should_write_every_release_target_and_dependent_once() {
+        let dir = std::env::temp_dir().join(format!("rema-write-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let pkgs = vec![
+            create_pkg_on_disk(&dir, "core", &[]),
+            create_pkg_on_disk(&dir, "react", &[("core", "^1.0.0")]),
+            create_pkg_on_disk(&dir, "ui", &[("core", "~1.0.0"), ("react", "1.0.0")]),
+        ];
+
+        let mut ctx = AppContext::new(vec![Release {
+            tag_name: "core@v1.0.0".to_string(),
+            ..Default::default()
+        }])
+        .unwrap();
+        ctx.set_and_match_local_pkg_files(pkgs).unwrap();
+        ctx.set_private_pkg_files(vec![create_pkg_on_disk(&dir, "app", &[("core", "^1.0.0")])]);
+        add_target(&mut ctx, "core", VersionBump::Minor);
+        add_target(&mut ctx, "react", VersionBump::Major);
+
+        let result = write_target_release_to_local_files(&ctx, None).unwrap();
+        let read = |name: &str| -> Value {
+            serde_json::from_str(&fs::read_to_string(dir.join(name).join("package.json")).unwrap())
+                .unwrap()
+        };
+        let (core, react, ui, app) = (read("core"), read("react"), read("ui"), read("app"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let WriteTargetResult::WritesCompleted { original_files } = result else {
+            panic!("Expected writes");
+        };
+        assert_eq!(original_files.len(), 4);
+        assert_eq!(core["version"], "1.1.0");
+        assert_eq!(react["version"], "2.0.0");
+        assert_eq!(react["dependencies"]["core"], "^1.1.0");
+        assert_eq!(ui["version"], "1.0.0");
+        assert_eq!(ui["dependencies"]["core"], "~1.1.0");
+        assert_eq!(ui["dependencies"]["react"], "2.0.0");
+        assert_eq!(app["version"], "1.0.0");
+        assert_eq!(app["dependencies"]["core"], "^1.1.0");
+    }
+
+    #[test]
+    fn should_only_change_updated_values_when_writing_json() {
+        let dir = std::env::temp_dir().join(format!("rema-write-json-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("package.json");
+        let original = r#"{
+    "version": "1.0.0",
+    "name": "core",
+    "scripts": {
+        "test": "vitest",
+        "build": "tsc"
+    },
+    "files": []
+}
+"#;
+        fs::write(&path, original).unwrap();
+
+        write_json(&path.to_string_lossy(), &[], |json| {
+            json["version"] = Value::String("1.1.0".to_string());
+        })
+        .unwrap();
+        let updated = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(updated, original.replace("1.0.0", "1.1.0"));
+    }
+
+    #[test]
+    fn should_write_and_back_up_lockfile_resyncs_with_the_release() {
+        let dir = std::env::temp_dir().join(format!("rema-write-resync-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let docs = create_pkg_on_disk(&dir, "docs", &[]);
+        let lockfile = dir.join("docs").join("package-lock.json");
+        let lock_contents = json!({ "name": "docs", "version": "0.9.0" }).to_string();
+        fs::write(&lockfile, &lock_contents).unwrap();
+
+        let mut ctx = AppContext::new(Vec::new()).unwrap();
+        ctx.add_lockfile_resync(LockfileMismatch {
+            pkg_name: Some("docs".to_string()),
+            package_json: docs.package_json.clone().unwrap(),
+            package_lock_json: PackageLockJson {
+                name: Some("docs".to_string()),
+                version: Some("0.9.0".to_string()),
+                path: Some(lockfile.to_string_lossy().to_string()),
+                workspace_key: None,
+            },
+        });
+        ctx.set_and_match_local_pkg_files(vec![docs]).unwrap();
+
+        let result = write_target_release_to_local_files(&ctx, None).unwrap();
+        let lock_after: Value =
+            serde_json::from_str(&fs::read_to_string(&lockfile).unwrap()).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let WriteTargetResult::WritesCompleted { original_files } = result else {
+            panic!("Expected writes");
+        };
+        assert_eq!(lock_after["version"], "1.0.0");
+        assert_eq!(original_files.len(), 1);
+        assert_eq!(original_files[0].contents, lock_contents);
+    }
+
+    #[test]
+    fn should_back_up_regenerated_lockfile_without_editing_it() {
+        let dir = std::env::temp_dir().join(format!("rema-write-regen-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut core = create_pkg_on_disk(&dir, "core", &[]);
+        let lockfile = dir.join("core").join("package-lock.json");
+        let lock_contents = json!({ "name": "core", "version": "1.0.0" }).to_string();
+        fs::write(&lockfile, &lock_contents).unwrap();
+        core.package_lock_json = Some(PackageLockJson {
+            name: Some("core".to_string()),
+            version: Some("1.0.0".to_string()),
+            path: Some(lockfile.to_string_lossy().to_string()),
+            workspace_key: None,
+        });
+
+        let mut ctx = AppContext::new(vec![Release {
+            tag_name: "core@v1.0.0".to_string(),
+            ..Default::default()
+        }])
+        .unwrap();
+        ctx.set_and_match_local_pkg_files(vec![core]).unwrap();
+        add_target(&mut ctx, "core", VersionBump::Minor);
+
+        let result = write_target_release_to_local_files(&ctx, Some(&lockfile)).unwrap();
+        let lock_after = fs::read_to_string(&lockfile).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let WriteTargetResult::WritesCompleted { original_files } = result else {
+            panic!("Expected writes");
+        };
+        assert_eq!(lock_after, lock_contents);
+        assert_eq!(original_files.len(), 2);
+        assert!(original_files
+            .iter()
+            .any(|original| original.path == lockfile.to_string_lossy()
+                && original.contents == lock_contents));
+    }
+
+    #[test]
+    fn should_list_files_to_update_per_package_without_writing() {
+        let dir = std::env::temp_dir().join(format!("rema-write-list-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let pkgs = vec![
+            create_pkg_on_disk(&dir, "core", &[]),
+            create_pkg_on_disk(&dir, "react", &[("core", "^1.0.0")]),
+            create_pkg_on_disk(&dir, "docs", &[]),
+        ];
+
+        let mut ctx = AppContext::new(vec![Release {
+            tag_name: "core@v1.0.0".to_string(),
+            ..Default::default()
+        }])
+        .unwrap();
+        ctx.set_and_match_local_pkg_files(pkgs).unwrap();
+        add_target(&mut ctx, "core", VersionBump::Minor);
+
+        let files = list_files_to_update(&ctx);
+        let core = fs::read_to_string(dir.join("core").join("package.json")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let pkg_names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(files.len(), 2);
+        assert!(pkg_names.contains(&"core"));
+        assert!(pkg_names.contains(&"react"));
+        assert!(core.contains("\"version\":\"1.0.0\""));
+    }
+
+    fn version_update(version: &str) -> PkgEntryUpdate {
+        PkgEntryUpdate {
+            version: Some(version.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_set_top_level_and_root_entry_version_in_own_lockfile() {
+        let mut lock = json!({
+            "name": "megatron",
+            "version": "0.0.4",
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "megatron", "version": "0.0.4" },
+                "node_modules/axios": { "version": "1.7.9" }
+            }
+        });
+
+        let entry = PkgEntry::Lockfile {
+            workspace_key: None,
+        };
+        apply_pkg_entry_update(&mut lock, &entry, &version_update("0.1.0"));
+
+        assert_eq!(lock["version"], "0.1.0");
+        assert_eq!(lock["packages"][""]["version"], "0.1.0");
+        assert_eq!(lock["packages"]["node_modules/axios"]["version"], "1.7.9");
+    }
+
+    #[test]
+    fn should_only_set_workspace_entry_version_in_root_lockfile() {
+        let mut lock = json!({
+            "name": "monorepo-test",
+            "version": "1.0.0",
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "monorepo-test", "version": "1.0.0" },
+                "packages/megatron": { "version": "0.0.4" },
+                "packages/optimus-prime": {}
+            }
+        });
+
+        let megatron = PkgEntry::Lockfile {
+            workspace_key: Some("packages/megatron".to_string()),
+        };
+        let optimus = PkgEntry::Lockfile {
+            workspace_key: Some("packages/optimus-prime".to_string()),
+        };
+        apply_pkg_entry_update(&mut lock, &megatron, &version_update("0.1.0"));
+        apply_pkg_entry_update(&mut lock, &optimus, &version_update("0.0.2"));
+
+        assert_eq!(lock["version"], "1.0.0");
+        assert_eq!(lock["packages"][""]["version"], "1.0.0");
+        assert_eq!(lock["packages"]["packages/megatron"]["version"], "0.1.0");
+        assert_eq!(
+            lock["packages"]["packages/optimus-prime"]["version"],
+            "0.0.2"
+        );
+    }
+
+    #[test]
+    fn should_leave_lockfile_v1_without_packages_untouched_except_version() {
+        let mut lock = json!({ "name": "megatron", "version": "0.0.4", "lockfileVersion": 1 });
+
+        let entry = PkgEntry::Lockfile {
+            workspace_key: None,
+        };
+        apply_pkg_entry_update(&mut lock, &entry, &version_update("0.0.5"));
+
+        assert_eq!(lock["version"], "0.0.5");
+        assert!(lock.get("packages").is_none());
+    }
+
+    #[test]
+    fn should_update_dependent_ranges_in_manifest_and_lockfile() {
+        let mut manifest = json!({
+            "name": "ui",
+            "version": "1.0.0",
+            "dependencies": { "core": "^1.0.0", "axios": "^1.7.9" },
+            "devDependencies": { "react": "workspace:~1.0.0" },
+            "peerDependencies": { "core": "workspace:*" }
+        });
+        let mut lock = json!({
+            "packages": {
+                "packages/ui": {
+                    "version": "1.0.0",
+                    "dependencies": { "core": "^1.0.0", "axios": "^1.7.9" }
+                }
+            }
+        });
+
+        let update = PkgEntryUpdate {
+            version: None,
+            dependency_versions: HashMap::from([
+                ("core".to_string(), Version::new(1, 1, 0)),
+                ("react".to_string(), Version::new(1, 0, 1)),
+            ]),
+        };
+        apply_pkg_entry_update(&mut manifest, &PkgEntry::PackageJson, &update);
+        let entry = PkgEntry::Lockfile {
+            workspace_key: Some("packages/ui".to_string()),
+        };
+        apply_pkg_entry_update(&mut lock, &entry, &update);
+
+        assert_eq!(manifest["version"], "1.0.0");
+        assert_eq!(manifest["dependencies"]["core"], "^1.1.0");
+        assert_eq!(manifest["dependencies"]["axios"], "^1.7.9");
+        assert_eq!(manifest["devDependencies"]["react"], "workspace:~1.0.1");
+        assert_eq!(manifest["peerDependencies"]["core"], "workspace:*");
+        assert_eq!(
+            lock["packages"]["packages/ui"]["dependencies"]["core"],
+            "^1.1.0"
+        );
+    }
+}